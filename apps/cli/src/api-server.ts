@@ -1,6 +1,7 @@
 import type { DatabaseType } from '@caw/core';
 import { createHttpHandler, createMcpServer } from '@caw/mcp-server';
 import { createBroadcaster, createRestApi, createWsHandler } from '@caw/rest-api';
+import pkg from '../package.json';
 
 export interface ApiServerOptions {
   port: number;
@@ -18,7 +19,7 @@ export async function runApiServer(db: DatabaseType, opts: ApiServerOptions): Pr
 
   // REST API with broadcaster
   const broadcaster = createBroadcaster();
-  const restApi = createRestApi(db, broadcaster, { repoPath: opts.repoPath });
+  const restApi = createRestApi(db, broadcaster, { repoPath: opts.repoPath, version: pkg.version });
 
   // WebSocket handler
   const wsHandler = createWsHandler(broadcaster);
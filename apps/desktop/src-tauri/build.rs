@@ -1,3 +1,30 @@
+use sha2::{Digest, Sha256};
+
 fn main() {
+    embed_sidecar_hash();
     tauri_build::build()
 }
+
+/// Expose the SHA-256 of the bundled sidecar as `CAW_SIDECAR_SHA256` so the
+/// app can detect a corrupted or swapped binary at runtime. Empty when the
+/// binary hasn't been built yet.
+fn embed_sidecar_hash() {
+    let target = std::env::var("TARGET").unwrap_or_default();
+    let ext = if target.contains("windows") {
+        ".exe"
+    } else {
+        ""
+    };
+    let path = format!("binaries/caw-{target}{ext}");
+    println!("cargo:rerun-if-changed={path}");
+
+    let hash = std::fs::read(&path)
+        .map(|bytes| {
+            Sha256::digest(bytes)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+    println!("cargo:rustc-env=CAW_SIDECAR_SHA256={hash}");
+}
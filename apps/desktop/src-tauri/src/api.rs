@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Default for API calls; planning and other long operations can be slow.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Health checks should fail fast rather than wait on a wedged server.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Bounds for a caller's timeout override: long enough to get an answer, short
+/// enough that a stuck request can't hold its slot indefinitely.
+const MIN_TIMEOUT: Duration = Duration::from_millis(100);
+const MAX_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Timeout for a proxied call: the caller's override (clamped), else a
+/// per-path default.
+fn request_timeout(path: &str, timeout_ms: Option<u64>) -> Duration {
+    match timeout_ms {
+        Some(ms) => Duration::from_millis(ms).clamp(MIN_TIMEOUT, MAX_TIMEOUT),
+        None if path == "/health" => HEALTH_TIMEOUT,
+        None => DEFAULT_TIMEOUT,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiResponse {
+    pub status: u16,
+    /// Parsed JSON when the body is JSON, otherwise the raw text.
+    pub body: serde_json::Value,
+}
+
+/// Build the sidecar URL for `path`, rejecting anything that could escape the
+/// server origin (absolute URLs, protocol-relative paths, userinfo tricks).
+pub fn sidecar_url(base_url: &str, path: &str) -> Result<reqwest::Url, String> {
+    if !path.starts_with('/') || path.starts_with("//") || path.contains('\\') {
+        return Err(format!("Path must be relative to the server root: {path}"));
+    }
+
+    let base = reqwest::Url::parse(base_url).map_err(|e| e.to_string())?;
+    let url = reqwest::Url::parse(&format!("{base_url}{path}")).map_err(|e| e.to_string())?;
+    if url.origin() != base.origin() {
+        return Err(format!("Path must stay on the caw server: {path}"));
+    }
+    Ok(url)
+}
+
+/// Forward a request to the sidecar from the backend, where CORS doesn't apply.
+pub async fn proxy(
+    client: &reqwest::Client,
+    base_url: &str,
+    method: &str,
+    path: &str,
+    body: Option<serde_json::Value>,
+    timeout_ms: Option<u64>,
+) -> Result<ApiResponse, String> {
+    let method = method.to_ascii_uppercase();
+    if !METHODS.contains(&method.as_str()) {
+        return Err(format!("Unsupported method: {method}"));
+    }
+    let method = reqwest::Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+    let url = sidecar_url(base_url, path)?;
+
+    let timeout = request_timeout(path, timeout_ms);
+    let mut request = client.request(method, url).timeout(timeout);
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+
+    let resp = request.send().await.map_err(|e| e.to_string())?;
+    let status = resp.status().as_u16();
+    let text = resp.text().await.map_err(|e| e.to_string())?;
+    let body = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+    Ok(ApiResponse { status, body })
+}
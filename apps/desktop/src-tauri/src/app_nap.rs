@@ -0,0 +1,57 @@
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::sync::Mutex;
+
+    use objc2::rc::Retained;
+    use objc2::runtime::{NSObjectProtocol, ProtocolObject};
+    use objc2_foundation::{NSActivityOptions, NSProcessInfo, NSString};
+
+    struct Activity(Retained<ProtocolObject<dyn NSObjectProtocol>>);
+
+    // Activity tokens are immutable and may be ended from any thread.
+    unsafe impl Send for Activity {}
+
+    static ACTIVITY: Mutex<Option<Activity>> = Mutex::new(None);
+
+    #[allow(unused_unsafe)]
+    pub fn begin() {
+        let Ok(mut activity) = ACTIVITY.lock() else {
+            return;
+        };
+        if activity.is_some() {
+            return;
+        }
+        let reason = NSString::from_str("Watching the caw server");
+        let token = unsafe {
+            NSProcessInfo::processInfo().beginActivityWithOptions_reason(
+                NSActivityOptions::UserInitiatedAllowingIdleSystemSleep,
+                &reason,
+            )
+        };
+        *activity = Some(Activity(token));
+    }
+
+    #[allow(unused_unsafe)]
+    pub fn end() {
+        let Ok(mut activity) = ACTIVITY.lock() else {
+            return;
+        };
+        if let Some(Activity(token)) = activity.take() {
+            unsafe { NSProcessInfo::processInfo().endActivity(&token) };
+        }
+    }
+}
+
+/// Opt out of macOS App Nap, which would otherwise throttle the health monitor
+/// and crash watcher while the app sits idle, until [`end`] is called.
+/// Repeated calls are no-ops, as is everything here outside macOS.
+pub fn begin() {
+    #[cfg(target_os = "macos")]
+    imp::begin();
+}
+
+/// Let macOS nap the app again.
+pub fn end() {
+    #[cfg(target_os = "macos")]
+    imp::end();
+}
@@ -0,0 +1,135 @@
+use tauri::{Runtime, WebviewWindow};
+#[cfg(target_os = "macos")]
+use window_vibrancy::{apply_vibrancy, clear_vibrancy, NSVisualEffectMaterial};
+#[cfg(target_os = "windows")]
+use window_vibrancy::{apply_acrylic, apply_mica, clear_acrylic, clear_mica};
+
+use crate::settings::{ActivationPolicy, DesktopSettings, Theme};
+
+/// Native theme to request for a window; `None` follows the OS appearance.
+fn native_theme(theme: Theme) -> Option<tauri::Theme> {
+    match theme {
+        Theme::Light => Some(tauri::Theme::Light),
+        Theme::Dark => Some(tauri::Theme::Dark),
+        Theme::System => None,
+    }
+}
+
+/// Apply `theme` to the window's native chrome (title bar and a matching
+/// vibrancy material on macOS, or Mica/acrylic on Windows).
+pub fn apply_theme<R: Runtime>(window: &WebviewWindow<R>, theme: Theme) {
+    let _ = window.set_theme(native_theme(theme));
+
+    let effective = native_theme(theme)
+        .or_else(|| window.theme().ok())
+        .unwrap_or(tauri::Theme::Light);
+    let _ = apply_vibrancy_for(window, effective);
+}
+
+/// Re-apply every settings-driven piece of window chrome. Each piece is
+/// attempted independently; failures are returned as `(setting, error)`.
+pub fn apply_appearance<R: Runtime>(
+    window: &WebviewWindow<R>,
+    settings: &DesktopSettings,
+) -> Vec<(&'static str, String)> {
+    let mut failures = Vec::new();
+    if let Err(e) = window.set_theme(native_theme(settings.theme)) {
+        failures.push(("theme", e.to_string()));
+    }
+    let effective = native_theme(settings.theme)
+        .or_else(|| window.theme().ok())
+        .unwrap_or(tauri::Theme::Light);
+    if let Err(e) = apply_vibrancy_for(window, effective) {
+        failures.push(("vibrancy", e));
+    }
+    if let Err(e) = window.set_always_on_top(settings.always_on_top) {
+        failures.push(("always_on_top", e.to_string()));
+    }
+    failures
+}
+
+/// `CAW_NO_VIBRANCY=1` swaps vibrancy for a solid background and keeps the
+/// standard traffic lights, for stable screenshots and UI tests.
+pub fn vibrancy_disabled() -> bool {
+    std::env::var("CAW_NO_VIBRANCY").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Show or hide the Dock icon. No-op outside macOS.
+pub fn apply_activation_policy<R: Runtime>(app: &tauri::AppHandle<R>, policy: ActivationPolicy) {
+    #[cfg(target_os = "macos")]
+    {
+        let policy = match policy {
+            ActivationPolicy::Regular => tauri::ActivationPolicy::Regular,
+            ActivationPolicy::Accessory => tauri::ActivationPolicy::Accessory,
+        };
+        let _ = app.set_activation_policy(policy);
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app, policy);
+}
+
+/// Re-apply theme-dependent chrome after the OS appearance changes.
+pub fn follow_system_theme<R: Runtime>(window: &WebviewWindow<R>, theme: tauri::Theme) {
+    let _ = apply_vibrancy_for(window, theme);
+}
+
+#[cfg(target_os = "macos")]
+fn apply_vibrancy_for<R: Runtime>(
+    window: &WebviewWindow<R>,
+    theme: tauri::Theme,
+) -> Result<(), String> {
+    let _ = clear_vibrancy(window);
+    if vibrancy_disabled() {
+        let solid = match theme {
+            tauri::Theme::Dark => tauri::window::Color(24, 24, 27, 255),
+            _ => tauri::window::Color(255, 255, 255, 255),
+        };
+        return window
+            .set_background_color(Some(solid))
+            .map_err(|e| e.to_string());
+    }
+    let material = match theme {
+        tauri::Theme::Dark => NSVisualEffectMaterial::HudWindow,
+        _ => NSVisualEffectMaterial::Sidebar,
+    };
+    apply_vibrancy(window, material, None, None).map_err(|e| e.to_string())
+}
+
+/// Mica on Windows 11, falling back to acrylic on Windows 10.
+#[cfg(target_os = "windows")]
+fn apply_vibrancy_for<R: Runtime>(
+    window: &WebviewWindow<R>,
+    theme: tauri::Theme,
+) -> Result<(), String> {
+    let _ = clear_mica(window);
+    let _ = clear_acrylic(window);
+    if vibrancy_disabled() {
+        return Ok(());
+    }
+    let dark = theme == tauri::Theme::Dark;
+    apply_mica(window, Some(dark))
+        .or_else(|_| {
+            let tint = if dark {
+                (24, 24, 27, 160)
+            } else {
+                (255, 255, 255, 160)
+            };
+            apply_acrylic(window, Some(tint))
+        })
+        .map_err(|e| e.to_string())
+}
+
+/// No blur effects on Linux; windows keep the default chrome.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn apply_vibrancy_for<R: Runtime>(
+    _window: &WebviewWindow<R>,
+    _theme: tauri::Theme,
+) -> Result<(), String> {
+    static LOGGED: std::sync::Once = std::sync::Once::new();
+    LOGGED.call_once(|| {
+        crate::logs::desktop_log!(
+            "Window vibrancy is not available on this platform; using default chrome"
+        );
+    });
+    Ok(())
+}
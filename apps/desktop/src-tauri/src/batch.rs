@@ -0,0 +1,163 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::deeplink;
+
+/// Maximum number of batch operations in flight against the sidecar at once.
+const MAX_PARALLEL: usize = 8;
+
+/// Per-operation timeout; execute may do real work before responding.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A single workflow operation, mapped onto the sidecar's REST endpoints.
+/// There is no delete: caw has no way to delete a workflow (not in the core
+/// services, REST API or CLI). Retire one with `SetStatus` to `"abandoned"`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BatchOp {
+    /// Start execution (also used to retry a failed workflow).
+    Execute {
+        id: String,
+    },
+    Suspend {
+        id: String,
+    },
+    Resume {
+        id: String,
+    },
+    /// Set the workflow status directly, e.g. `"abandoned"` to retire it.
+    SetStatus {
+        id: String,
+        status: String,
+        reason: Option<String>,
+    },
+}
+
+impl BatchOp {
+    fn id(&self) -> &str {
+        match self {
+            Self::Execute { id }
+            | Self::Suspend { id }
+            | Self::Resume { id }
+            | Self::SetStatus { id, .. } => id,
+        }
+    }
+
+    /// The request for this operation. Fails for an id that isn't a plain
+    /// workflow id, which could otherwise reach a different route.
+    fn request(
+        &self,
+        client: &reqwest::Client,
+        base_url: &str,
+    ) -> Result<reqwest::RequestBuilder, String> {
+        let id = self.id();
+        if !deeplink::is_valid_id(id) {
+            return Err(format!("Invalid workflow id: {id:?}"));
+        }
+        let url = |action: &str| format!("{base_url}/api/workflows/{id}/{action}");
+        Ok(match self {
+            Self::Execute { .. } => client.post(url("execute")),
+            Self::Suspend { .. } => client.post(url("suspend")),
+            Self::Resume { .. } => client.post(url("resume")),
+            Self::SetStatus { status, reason, .. } => client
+                .put(url("status"))
+                .json(&serde_json::json!({ "status": status, "reason": reason })),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchResult {
+    pub id: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+async fn run_one(client: &reqwest::Client, base_url: &str, op: &BatchOp) -> BatchResult {
+    let error = match op.request(client, base_url) {
+        Ok(request) => match request.timeout(REQUEST_TIMEOUT).send().await {
+            Ok(resp) if resp.status().is_success() => None,
+            Ok(resp) => {
+                let status = resp.status();
+                let body = resp.text().await.unwrap_or_default();
+                Some(format!("{status}: {body}"))
+            }
+            Err(e) => Some(e.to_string()),
+        },
+        Err(e) => Some(e),
+    };
+    BatchResult {
+        id: op.id().to_string(),
+        ok: error.is_none(),
+        error,
+    }
+}
+
+/// Run every operation with bounded parallelism. Failures are reported per
+/// operation and never abort the rest of the batch; results keep input order.
+pub async fn run_batch(
+    client: reqwest::Client,
+    base_url: &str,
+    ops: Vec<BatchOp>,
+) -> Vec<BatchResult> {
+    let permits = Arc::new(Semaphore::new(MAX_PARALLEL));
+    let handles: Vec<_> = ops
+        .into_iter()
+        .map(|op| {
+            let client = client.clone();
+            let base_url = base_url.to_string();
+            let permits = permits.clone();
+            let id = op.id().to_string();
+            let handle = tauri::async_runtime::spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                run_one(&client, &base_url, &op).await
+            });
+            (id, handle)
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for (id, handle) in handles {
+        results.push(handle.await.unwrap_or_else(|e| BatchResult {
+            id,
+            ok: false,
+            error: Some(format!("Operation task failed: {e}")),
+        }));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE_URL: &str = "http://localhost:3100";
+
+    fn url(op: BatchOp) -> Result<String, String> {
+        let request = op.request(&reqwest::Client::new(), BASE_URL)?;
+        Ok(request.build().map_err(|e| e.to_string())?.url().to_string())
+    }
+
+    #[test]
+    fn builds_workflow_urls() {
+        let id = "wf_0a1b2c3d4e5f".to_string();
+        assert_eq!(
+            url(BatchOp::Execute { id: id.clone() }).unwrap(),
+            "http://localhost:3100/api/workflows/wf_0a1b2c3d4e5f/execute"
+        );
+        assert_eq!(
+            url(BatchOp::SetStatus { id, status: "abandoned".into(), reason: None }).unwrap(),
+            "http://localhost:3100/api/workflows/wf_0a1b2c3d4e5f/status"
+        );
+    }
+
+    #[test]
+    fn rejects_ids_that_would_leave_the_workflow_route() {
+        for id in ["", "..", "wf_1/tasks", "wf_1?x=1", "wf_1#x", "wf 1"] {
+            assert!(url(BatchOp::Resume { id: id.to_string() }).is_err(), "{id:?}");
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+/// Upper bounds so a benchmark can't flood the sidecar or the app.
+pub const MAX_REQUESTS: usize = 10_000;
+pub const MAX_CONCURRENCY: usize = 64;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub requests: usize,
+    pub concurrency: usize,
+    pub total_ms: u64,
+    /// Latency percentiles of successful requests, in milliseconds.
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    /// Successful requests per second over the whole run.
+    pub rps: f64,
+    pub errors: usize,
+}
+
+/// Nearest-rank percentile of sorted `latencies`.
+fn percentile(latencies: &[f64], pct: f64) -> f64 {
+    if latencies.is_empty() {
+        return 0.0;
+    }
+    let rank = (pct / 100.0 * latencies.len() as f64).ceil() as usize;
+    latencies[rank.clamp(1, latencies.len()) - 1]
+}
+
+/// GET `url` `requests` times with at most `concurrency` in flight.
+pub async fn run(
+    client: reqwest::Client,
+    url: reqwest::Url,
+    requests: usize,
+    concurrency: usize,
+) -> Result<BenchReport, String> {
+    if requests == 0 || requests > MAX_REQUESTS {
+        return Err(format!("requests must be between 1 and {MAX_REQUESTS}"));
+    }
+    if concurrency == 0 || concurrency > MAX_CONCURRENCY {
+        return Err(format!(
+            "concurrency must be between 1 and {MAX_CONCURRENCY}"
+        ));
+    }
+
+    let permits = Arc::new(Semaphore::new(concurrency));
+    let started = Instant::now();
+    let handles: Vec<_> = (0..requests)
+        .map(|_| {
+            let client = client.clone();
+            let url = url.clone();
+            let permits = permits.clone();
+            tauri::async_runtime::spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let start = Instant::now();
+                match client.get(url).timeout(REQUEST_TIMEOUT).send().await {
+                    Ok(resp) if resp.status().is_success() => {
+                        let _ = resp.bytes().await;
+                        Some(start.elapsed().as_secs_f64() * 1000.0)
+                    }
+                    _ => None,
+                }
+            })
+        })
+        .collect();
+
+    let mut latencies = Vec::with_capacity(requests);
+    for handle in handles {
+        if let Ok(Some(ms)) = handle.await {
+            latencies.push(ms);
+        }
+    }
+    let total = started.elapsed();
+    latencies.sort_by(f64::total_cmp);
+
+    Ok(BenchReport {
+        requests,
+        concurrency,
+        total_ms: total.as_millis() as u64,
+        p50: percentile(&latencies, 50.0),
+        p95: percentile(&latencies, 95.0),
+        p99: percentile(&latencies, 99.0),
+        rps: latencies.len() as f64 / total.as_secs_f64().max(f64::EPSILON),
+        errors: requests - latencies.len(),
+    })
+}
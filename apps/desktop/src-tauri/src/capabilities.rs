@@ -0,0 +1,68 @@
+use serde::Serialize;
+
+/// Feature names understood by the desktop app.
+pub const CHECKPOINT: &str = "checkpoint";
+pub const MIGRATE: &str = "migrate";
+pub const INTEGRITY_CHECK: &str = "integrity_check";
+
+/// What a running sidecar reports it supports.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Capabilities {
+    pub api_version: Option<String>,
+    /// The sidecar build's version, if it reports one.
+    pub version: Option<String>,
+    pub features: Vec<String>,
+    /// False when the sidecar predates `/api/capabilities`, in which case support
+    /// for any feature is unknown rather than absent.
+    pub reported: bool,
+}
+
+impl Capabilities {
+    /// `Some(bool)` when the sidecar reported its features, `None` if unknown.
+    pub fn supports(&self, feature: &str) -> Option<bool> {
+        self.reported
+            .then(|| self.features.iter().any(|f| f == feature))
+    }
+
+    /// Error for a feature the sidecar is known not to support.
+    pub fn require(&self, feature: &str) -> Result<(), String> {
+        match self.supports(feature) {
+            Some(false) => Err(format!("{feature} is not supported by this server version")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Ask the sidecar for `GET /api/capabilities`. A 404 means an older server
+/// that doesn't report them.
+pub async fn fetch(client: &reqwest::Client, url: &str) -> Result<Capabilities, String> {
+    let resp = client
+        .get(url)
+        .timeout(std::time::Duration::from_secs(2))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Capabilities::default());
+    }
+    if !resp.status().is_success() {
+        return Err(format!("Server returned {}", resp.status()));
+    }
+
+    let body: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    let data = &body["data"];
+    let features = data["features"]
+        .as_array()
+        .map(|list| {
+            list.iter()
+                .filter_map(|f| f.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    Ok(Capabilities {
+        api_version: data["api_version"].as_str().map(str::to_string),
+        version: data["version"].as_str().map(str::to_string),
+        features,
+        reported: true,
+    })
+}
@@ -0,0 +1,649 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// How the active database path was chosen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DbMode {
+    /// `<repo_root>/.caw/workflows.db`
+    Project,
+    /// `~/.caw/workflows.db`
+    Global,
+    /// An explicit path picked by the user.
+    Custom,
+    /// A scratch database in the temp dir, deleted when the app exits.
+    Temporary,
+}
+
+impl DbMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "project" => Ok(Self::Project),
+            "global" => Ok(Self::Global),
+            "custom" => Ok(Self::Custom),
+            "temporary" => Ok(Self::Temporary),
+            other => Err(format!("Unknown database mode: {other}")),
+        }
+    }
+}
+
+/// A database path together with the rule that produced it.
+#[derive(Clone, Debug)]
+pub struct ResolvedDb {
+    pub path: String,
+    pub mode: DbMode,
+}
+
+/// Top-level directory of the git repo containing the current directory, if any.
+pub fn git_root() -> Option<String> {
+    git_root_in(Path::new("."))
+}
+
+/// Top-level directory of the git repo containing `dir`, if any.
+pub fn git_root_in(dir: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let repo_root = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!repo_root.is_empty()).then_some(repo_root)
+}
+
+/// Root of the checkout whose `.caw` holds the project database: the main
+/// working tree, so every linked worktree of a repo shares one database.
+fn project_root() -> Option<String> {
+    let repo_root = git_root()?;
+    Some(
+        worktree_common_dir(Path::new("."))
+            .and_then(|common_dir| main_worktree_root(&common_dir))
+            .unwrap_or(repo_root),
+    )
+}
+
+pub fn project_db_path() -> Option<String> {
+    project_root().map(|root| format!("{root}/.caw/workflows.db"))
+}
+
+/// `~/.caw`, home of the global database and desktop settings.
+pub fn caw_home() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".caw")
+}
+
+pub fn global_db_path() -> String {
+    caw_home()
+        .join("workflows.db")
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Expand a leading `~` or `~/` to `$HOME`. The result is a plain path string,
+/// never quoted or escaped, since it is passed to the sidecar as one argv element.
+pub fn expand_home(path: &str) -> String {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => rest,
+        _ => return path.to_string(),
+    };
+    match std::env::var("HOME") {
+        Ok(home) if !home.is_empty() => format!("{home}{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+/// Which resolution rule picked the database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DbSource {
+    /// Pinned in `desktop.json` by a custom-path choice in the app.
+    Persisted,
+    /// `CAW_DB` or `CAW_DB_PATH`.
+    Env,
+    /// The main checkout of the git repo around the working directory.
+    GitRoot,
+    /// A linked worktree (`git worktree add`), sharing its main working tree's database.
+    Worktree,
+    /// The database used at the last launch, reopened outside a git repo.
+    LastUsed,
+    Global,
+}
+
+/// Why `resolve_db_path` chose the path it did.
+#[derive(Clone, Debug, Serialize)]
+pub struct Provenance {
+    pub path: String,
+    pub source: DbSource,
+    /// The inputs the winning rule used, e.g. the git root it found.
+    pub details: serde_json::Value,
+}
+
+/// The main working tree for a repo's shared git dir: the parent of `.git`.
+/// `None` for bare repos, whose worktrees keep their own databases.
+fn main_worktree_root(common_dir: &str) -> Option<String> {
+    let common_dir = Path::new(common_dir);
+    if common_dir.file_name()? != ".git" {
+        return None;
+    }
+    Some(common_dir.parent()?.to_string_lossy().into_owned())
+}
+
+/// The repo's shared `.git` directory when `dir` is inside a linked worktree
+/// rather than the main checkout.
+fn worktree_common_dir(dir: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--git-dir", "--git-common-dir"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Either may be relative to `dir`
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines().map(|line| std::fs::canonicalize(dir.join(line.trim())).ok());
+    match (lines.next()??, lines.next()??) {
+        (git_dir, common) if git_dir != common => Some(common.to_string_lossy().into_owned()),
+        _ => None,
+    }
+}
+
+/// Variables that name the database outright, checked in order. `CAW_DB` is
+/// an alias of `CAW_DB_PATH`, the name the CLI and MCP server read.
+const DB_ENV_VARS: [&str; 2] = ["CAW_DB", "CAW_DB_PATH"];
+
+/// The first of `CAW_DB`/`CAW_DB_PATH` that is set and non-empty, with its raw
+/// (unexpanded) value.
+pub fn env_db_override() -> Option<(&'static str, String)> {
+    DB_ENV_VARS.into_iter().find_map(|var| {
+        let raw = std::env::var(var).ok().filter(|v| !v.trim().is_empty())?;
+        Some((var, raw))
+    })
+}
+
+/// Create the directory `db_path` lives in, e.g. on a fresh external volume,
+/// so the sidecar doesn't fail to open it. A failure is only logged; the
+/// write probe that follows reports it.
+fn create_parent_dir(db_path: &str) {
+    if let Some(dir) = Path::new(db_path).parent().filter(|d| !d.as_os_str().is_empty()) {
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            crate::logs::desktop_log!("Warning: cannot create {}: {e}", dir.display());
+        }
+    }
+}
+
+/// Resolve the database path for the sidecar, and record which rule won.
+/// 1. Use `CAW_DB` (or its alias `CAW_DB_PATH`) if set (custom mode), with
+///    `~` expanded and its directory created
+/// 2. Use `pinned` (the `db_path` saved in desktop settings) if set
+/// 3. Try `git rev-parse --show-toplevel` → `<repo_root>/.caw/workflows.db`,
+///    where a linked worktree uses its main working tree's root
+/// 4. Reopen `last_used` (the database from the previous launch) if it still exists
+/// 5. Fall back to `~/.caw/workflows.db` (global mode)
+pub fn resolve_db_path(
+    pinned: Option<&str>,
+    last_used: Option<&str>,
+) -> (ResolvedDb, Provenance) {
+    resolve_db_path_in(Path::new("."), pinned, last_used)
+}
+
+/// [`resolve_db_path`] with the git rules applied to the repo around `dir`
+/// instead of the working directory.
+pub fn resolve_db_path_in(
+    dir: &Path,
+    pinned: Option<&str>,
+    last_used: Option<&str>,
+) -> (ResolvedDb, Provenance) {
+    let resolved = |path: String, mode, source, details| {
+        let provenance = Provenance {
+            path: path.clone(),
+            source,
+            details,
+        };
+        (ResolvedDb { path, mode }, provenance)
+    };
+
+    if let Some((var, raw)) = env_db_override() {
+        let path = expand_home(&raw);
+        create_parent_dir(&path);
+        let details = serde_json::json!({ "variable": var, "value": raw });
+        return resolved(path, DbMode::Custom, DbSource::Env, details);
+    }
+    if let Some(path) = pinned {
+        let details = serde_json::json!({ "settings": crate::settings::settings_path() });
+        return resolved(path.to_string(), DbMode::Custom, DbSource::Persisted, details);
+    }
+    if let Some(repo_root) = git_root_in(dir) {
+        let path = |root: &str| format!("{root}/.caw/workflows.db");
+        let worktree = worktree_common_dir(dir)
+            .map(|common_dir| (main_worktree_root(&common_dir), common_dir));
+        return match worktree {
+            Some((main_root, common_dir)) => {
+                let details = serde_json::json!({
+                    "git_root": repo_root,
+                    "git_common_dir": common_dir,
+                    "main_worktree": main_root,
+                });
+                let root = main_root.as_deref().unwrap_or(&repo_root);
+                resolved(path(root), DbMode::Project, DbSource::Worktree, details)
+            }
+            None => {
+                let details = serde_json::json!({ "git_root": repo_root });
+                resolved(path(&repo_root), DbMode::Project, DbSource::GitRoot, details)
+            }
+        };
+    }
+    let global = global_db_path();
+    if let Some(path) = last_used.filter(|p| *p != global && Path::new(p).exists()) {
+        let details = serde_json::json!({ "settings": crate::settings::settings_path() });
+        return resolved(path.to_string(), DbMode::Custom, DbSource::LastUsed, details);
+    }
+    let details = serde_json::json!({ "caw_home": caw_home() });
+    resolved(global, DbMode::Global, DbSource::Global, details)
+}
+
+/// Check that the directory holding `db_path` can be created and written to by
+/// creating (and removing) a probe file in it, or in its nearest existing
+/// ancestor when it doesn't exist yet. No directories are created.
+pub fn probe_writable(db_path: &str) -> Result<(), String> {
+    let parent = Path::new(db_path)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    // Probe without creating anything: a missing directory can be created
+    // if its nearest existing ancestor is writable
+    let dir = parent
+        .ancestors()
+        .find(|dir| !dir.as_os_str().is_empty() && dir.exists())
+        .unwrap_or(Path::new("."));
+    if !dir.is_dir() {
+        let (parent, dir) = (parent.display(), dir.display());
+        return Err(format!("Cannot create {parent}: {dir} is not a directory"));
+    }
+
+    let probe = dir.join(format!(".caw-write-probe-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| format!("{} is not writable: {e}", dir.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// How long the write probe waits for another connection's write lock.
+const WRITE_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Confirm the database accepts writes by taking SQLite's write lock and
+/// creating a table inside a transaction that is rolled back. Unlike
+/// `probe_writable` this catches read-only files and locks held elsewhere.
+pub fn check_db_writable(db_path: &str) -> Result<(), String> {
+    if !Path::new(db_path).exists() {
+        // The sidecar creates it on first open
+        return probe_writable(db_path);
+    }
+    let explain = |e: rusqlite::Error| match e.sqlite_error_code() {
+        Some(rusqlite::ErrorCode::ReadOnly) => {
+            "The database is read-only (file permissions or a read-only mount)".to_string()
+        }
+        Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+            "The database is locked by another process".to_string()
+        }
+        Some(rusqlite::ErrorCode::CannotOpen | rusqlite::ErrorCode::PermissionDenied) => {
+            format!("The database cannot be opened for writing: {e}")
+        }
+        _ => format!("Write check failed: {e}"),
+    };
+
+    let mut conn =
+        rusqlite::Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE)
+            .map_err(explain)?;
+    conn.busy_timeout(WRITE_PROBE_TIMEOUT).map_err(explain)?;
+    let readonly = conn
+        .is_readonly(rusqlite::DatabaseName::Main)
+        .map_err(explain)?;
+    if readonly {
+        return Err("The database opened read-only".to_string());
+    }
+    let tx = conn
+        .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+        .map_err(explain)?;
+    tx.execute("CREATE TABLE caw_desktop_write_probe (x)", [])
+        .map_err(explain)?;
+    tx.rollback().map_err(explain)
+}
+
+/// Validate a user-supplied database path before switching the sidecar to it.
+pub fn validate_db_path(path: &str) -> Result<(), String> {
+    if Path::new(path).is_dir() {
+        return Err(format!("{path} is a directory, not a database file"));
+    }
+    probe_writable(path)
+}
+
+/// Rename the database file and its `-wal`/`-shm` companions to `<new_name>.db`
+/// in the same directory, refusing to overwrite anything. Returns the new path.
+pub fn rename_db_files(db_path: &str, new_name: &str) -> Result<String, String> {
+    let name = new_name.trim();
+    let name = name.strip_suffix(".db").unwrap_or(name);
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(format!("Invalid database name: {new_name:?}"));
+    }
+
+    let old = Path::new(db_path);
+    if !old.is_file() {
+        return Err(format!("Database file not found: {db_path}"));
+    }
+    let new = old.with_file_name(format!("{name}.db"));
+    if new == old {
+        return Err(format!("Database is already named {name}.db"));
+    }
+
+    for suffix in ["", "-wal", "-shm"] {
+        let target = companion(&new, suffix);
+        if target.exists() {
+            return Err(format!("{} already exists", target.display()));
+        }
+    }
+
+    // On failure, move back what was already renamed so the database and its
+    // WAL never end up under different names
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for suffix in ["", "-wal", "-shm"] {
+        let from = companion(old, suffix);
+        if !suffix.is_empty() && !from.exists() {
+            continue;
+        }
+        let to = companion(&new, suffix);
+        if let Err(e) = std::fs::rename(&from, &to) {
+            for (from, to) in moved.iter().rev() {
+                if let Err(undo) = std::fs::rename(to, from) {
+                    crate::logs::desktop_log!(
+                        "Warning: could not move {} back to {}: {undo}",
+                        to.display(),
+                        from.display()
+                    );
+                }
+            }
+            return Err(format!("Failed to rename {}: {e}", from.display()));
+        }
+        moved.push((from, to));
+    }
+    Ok(new.to_string_lossy().into_owned())
+}
+
+/// Result of `repair_lock`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum LockRepair {
+    /// No `-wal`/`-shm` files were left behind; nothing to do.
+    Clean,
+    /// Another process has the database open, so its WAL is live, not stale.
+    InUse { pids: Vec<u32> },
+    /// Stale files were found but not touched because `confirm` was false.
+    NeedsConfirmation { files: Vec<String> },
+    Repaired {
+        backup: String,
+        removed: Vec<String>,
+    },
+}
+
+fn companion(path: &Path, suffix: &str) -> PathBuf {
+    let mut os = path.as_os_str().to_owned();
+    os.push(suffix);
+    PathBuf::from(os)
+}
+
+/// PIDs of processes that have the database or its companions open.
+#[cfg(unix)]
+fn open_by(paths: &[PathBuf]) -> Result<Vec<u32>, String> {
+    let output = std::process::Command::new("lsof")
+        .arg("-t")
+        .args(paths)
+        .output()
+        .map_err(|e| format!("Cannot check whether the database is in use: {e}"))?;
+    let own = std::process::id();
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .filter(|pid| *pid != own)
+        .collect())
+}
+
+/// Windows refuses to delete files that are open, which is check enough.
+#[cfg(not(unix))]
+fn open_by(_paths: &[PathBuf]) -> Result<Vec<u32>, String> {
+    Ok(Vec::new())
+}
+
+/// Recover from a "database is locked" state left by an unclean shutdown.
+///
+/// If nothing holds the database, back up the db and its `-wal`/`-shm` files,
+/// open it once to check integrity and fold the WAL back in, then remove
+/// whichever companions SQLite left behind. Nothing is touched unless `confirm`.
+pub fn repair_lock(db_path: &str, confirm: bool) -> Result<LockRepair, String> {
+    let db = Path::new(db_path);
+    if !db.is_file() {
+        return Err(format!("Database file not found: {db_path}"));
+    }
+    let stale: Vec<PathBuf> = ["-wal", "-shm"]
+        .iter()
+        .map(|suffix| companion(db, suffix))
+        .filter(|path| path.exists())
+        .collect();
+    if stale.is_empty() {
+        return Ok(LockRepair::Clean);
+    }
+
+    let mut watched = vec![db.to_path_buf()];
+    watched.extend(stale.iter().cloned());
+    let pids = open_by(&watched)?;
+    if !pids.is_empty() {
+        return Ok(LockRepair::InUse { pids });
+    }
+
+    let display = |paths: &[PathBuf]| -> Vec<String> {
+        paths
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect()
+    };
+    if !confirm {
+        return Ok(LockRepair::NeedsConfirmation {
+            files: display(&stale),
+        });
+    }
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let backup = companion(db, &format!(".lock-backup-{stamp}"));
+    std::fs::create_dir_all(&backup)
+        .map_err(|e| format!("Cannot create backup at {}: {e}", backup.display()))?;
+    for path in &watched {
+        let Some(name) = path.file_name() else {
+            continue;
+        };
+        std::fs::copy(path, backup.join(name))
+            .map_err(|e| format!("Failed to back up {}: {e}", path.display()))?;
+    }
+
+    let conn = rusqlite::Connection::open(db).map_err(|e| e.to_string())?;
+    let check: String = conn
+        .query_row("PRAGMA quick_check", [], |row| row.get(0))
+        .map_err(|e| match e.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked) => {
+                "The database appears to be genuinely in use".to_string()
+            }
+            _ => format!("Database failed to open cleanly: {e}"),
+        })?;
+    if check != "ok" {
+        return Err(format!("Database integrity check failed: {check}"));
+    }
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+        .map_err(|e| format!("Failed to checkpoint WAL: {e}"))?;
+    conn.close().map_err(|(_, e)| e.to_string())?;
+
+    let mut removed = Vec::new();
+    for path in &stale {
+        if path.exists() {
+            std::fs::remove_file(path)
+                .map_err(|e| format!("Failed to remove {}: {e}", path.display()))?;
+        }
+        removed.push(path.to_string_lossy().into_owned());
+    }
+    Ok(LockRepair::Repaired {
+        backup: backup.to_string_lossy().into_owned(),
+        removed,
+    })
+}
+
+/// A fresh scratch database path under the system temp dir.
+pub fn temp_db_path() -> String {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    std::env::temp_dir()
+        .join(format!("caw-temp-{}-{stamp}.db", std::process::id()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Delete a database file and its `-wal`/`-shm` companions, ignoring any
+/// that don't exist.
+pub fn remove_db_files(db_path: &str) {
+    let db = Path::new(db_path);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(companion(db, suffix));
+    }
+}
+
+/// Write a consistent single-file copy of `src` to `dest` with `VACUUM INTO`.
+pub fn copy_db(src: &str, dest: &str) -> Result<(), String> {
+    if Path::new(dest).exists() {
+        return Err(format!("{dest} already exists"));
+    }
+    validate_db_path(dest)?;
+    let conn = rusqlite::Connection::open(src).map_err(|e| e.to_string())?;
+    conn.execute("VACUUM INTO ?1", [dest])
+        .map_err(|e| format!("Failed to save database to {dest}: {e}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Tests here read or set process-wide environment variables.
+    static ENV: Mutex<()> = Mutex::new(());
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("caw-db-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn expand_home_replaces_a_leading_tilde() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let home = std::env::var("HOME").expect("HOME is set");
+        assert_eq!(expand_home("~"), home);
+        assert_eq!(expand_home("~/.caw/workflows.db"), format!("{home}/.caw/workflows.db"));
+        assert_eq!(expand_home("~/my dbs/ワーク.db"), format!("{home}/my dbs/ワーク.db"));
+    }
+
+    #[test]
+    fn expand_home_leaves_other_paths_alone() {
+        assert_eq!(expand_home("~other/workflows.db"), "~other/workflows.db");
+        assert_eq!(expand_home("/srv/~/workflows.db"), "/srv/~/workflows.db");
+        assert_eq!(expand_home("relative/workflows.db"), "relative/workflows.db");
+    }
+
+    #[test]
+    fn git_root_in_is_none_outside_a_repo() {
+        let dir = std::env::temp_dir().join(format!("caw-no-repo-{}", std::process::id()));
+        assert_eq!(git_root_in(&dir), None);
+    }
+
+    #[test]
+    fn resolve_db_path_in_a_directory_outside_a_repo_falls_back_to_global() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::remove_var("CAW_DB");
+        std::env::remove_var("CAW_DB_PATH");
+        let dir = std::env::temp_dir().join(format!("caw-no-repo-{}", std::process::id()));
+
+        let (db, provenance) = resolve_db_path_in(&dir, None, None);
+
+        assert_eq!(db.path, global_db_path());
+        assert_eq!(db.mode, DbMode::Global);
+        assert_eq!(provenance.source, DbSource::Global);
+    }
+
+    #[test]
+    fn probe_writable_creates_no_directories() {
+        let root = std::env::temp_dir().join(format!("caw-probe-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let missing = root.join("a").join("b");
+
+        let probed = probe_writable(&missing.join("workflows.db").to_string_lossy());
+
+        assert_eq!(probed, Ok(()));
+        assert!(!root.join("a").exists());
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn probe_writable_rejects_a_file_in_the_way() {
+        let root = std::env::temp_dir().join(format!("caw-probe-file-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("file"), b"").unwrap();
+
+        let probed = probe_writable(&root.join("file/sub/workflows.db").to_string_lossy());
+
+        assert!(probed.is_err_and(|e| e.contains("is not a directory")));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn env_path_with_tilde_spaces_and_unicode() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let home = scratch_dir("home");
+        let saved_home = std::env::var("HOME").ok();
+        std::env::set_var("HOME", &home);
+        std::env::remove_var("CAW_DB_PATH");
+        std::env::set_var("CAW_DB", "~/my dbs/données é/ワーク.db");
+
+        let (db, provenance) = resolve_db_path(Some("/pinned/workflows.db"), None);
+
+        std::env::remove_var("CAW_DB");
+        match saved_home {
+            Some(saved) => std::env::set_var("HOME", saved),
+            None => std::env::remove_var("HOME"),
+        }
+        let expected = format!("{}/my dbs/données é/ワーク.db", home.display());
+        assert_eq!(db.path, expected);
+        assert_eq!(db.mode, DbMode::Custom);
+        assert_eq!(provenance.source, DbSource::Env);
+        assert!(home.join("my dbs").join("données é").is_dir());
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn pinned_path_with_spaces_and_unicode_is_kept_verbatim() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::remove_var("CAW_DB");
+        std::env::remove_var("CAW_DB_PATH");
+        let pinned = "/Users/Zoë Müller/Project Files/工作流.db";
+
+        let (db, provenance) = resolve_db_path(Some(pinned), None);
+
+        assert_eq!(db.path, pinned);
+        assert_eq!(db.mode, DbMode::Custom);
+        assert_eq!(provenance.source, DbSource::Persisted);
+    }
+}
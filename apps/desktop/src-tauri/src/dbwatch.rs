@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Quiet period after the last event before reacting, so an atomic-rename
+/// save (write temp, rename over) restarts the sidecar once, not per event.
+pub const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Whether `event` removed, renamed, or replaced the file at `db`.
+fn touches_db(event: &Event, db: &Path) -> bool {
+    let relevant = matches!(
+        event.kind,
+        EventKind::Remove(_) | EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+    );
+    relevant && event.paths.iter().any(|path| path == db)
+}
+
+/// Watch the database at `db_path` and call `on_change` once things settle
+/// after it is deleted, moved, or replaced. The directory is watched rather
+/// than the file, which would stop reporting once deleted. Dropping the
+/// returned watcher stops it without a final call.
+pub fn watch(
+    db_path: &str,
+    on_change: impl Fn() + Send + 'static,
+) -> Result<RecommendedWatcher, String> {
+    let path = Path::new(db_path);
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(format!("{db_path} has no parent directory"));
+    };
+    // Events report resolved paths (e.g. /private/var on macOS)
+    let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let db: PathBuf = dir.join(name);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if event.is_ok_and(|event| touches_db(&event, &db)) {
+            let _ = tx.send(());
+        }
+    })
+    .map_err(|e| e.to_string())?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            loop {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(()) => continue,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            on_change();
+        }
+    });
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind, RenameMode};
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn removal_rename_and_replacement_touch_the_db() {
+        let db = Path::new("/data/.caw/workflows.db");
+        let kinds = [
+            EventKind::Remove(RemoveKind::File),
+            EventKind::Create(CreateKind::File),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Any)),
+        ];
+        for kind in kinds {
+            assert!(touches_db(&event(kind, "/data/.caw/workflows.db"), db), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn writes_and_other_files_do_not() {
+        let db = Path::new("/data/.caw/workflows.db");
+        let write = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        assert!(!touches_db(&event(write, "/data/.caw/workflows.db"), db));
+        let removed = EventKind::Remove(RemoveKind::File);
+        assert!(!touches_db(&event(removed, "/data/.caw/workflows.db-wal"), db));
+    }
+}
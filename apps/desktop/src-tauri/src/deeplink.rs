@@ -0,0 +1,59 @@
+/// The custom URL scheme registered for the app, as in `caw://workflow/<id>`.
+pub const SCHEME: &str = "caw";
+
+/// Map a `caw://` URL to the frontend route it opens, e.g.
+/// `caw://workflow/wf_123` → `/workflows/wf_123`.
+pub fn route(url: &str) -> Result<String, String> {
+    let rest = url
+        .strip_prefix(SCHEME)
+        .and_then(|rest| rest.strip_prefix("://"))
+        .ok_or_else(|| format!("Not a {SCHEME}:// URL: {url}"))?;
+    // Query strings and fragments carry nothing the routes use
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let mut parts = rest.trim_end_matches('/').split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("workflow"), Some(id), None) if is_valid_id(id) => Ok(format!("/workflows/{id}")),
+        _ => Err(format!("Unrecognized link: {url}")),
+    }
+}
+
+/// Whether `id` looks like a caw id (`wf_…`), so it is safe as a URL path segment.
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_workflow_links() {
+        assert_eq!(
+            route("caw://workflow/wf_abc123def456"),
+            Ok("/workflows/wf_abc123def456".into())
+        );
+        assert_eq!(
+            route("caw://workflow/wf_abc123def456/"),
+            Ok("/workflows/wf_abc123def456".into())
+        );
+        assert_eq!(route("caw://workflow/wf_abc?tab=tasks#top"), Ok("/workflows/wf_abc".into()));
+    }
+
+    #[test]
+    fn rejects_other_schemes_and_routes() {
+        assert!(route("https://workflow/wf_abc").is_err());
+        assert!(route("caw:workflow/wf_abc").is_err());
+        assert!(route("caw://workflow/").is_err());
+        assert!(route("caw://workflow/wf_abc/tasks").is_err());
+        assert!(route("caw://task/tk_abc").is_err());
+    }
+
+    #[test]
+    fn rejects_ids_that_are_not_plain_segments() {
+        assert!(route("caw://workflow/..").is_err());
+        assert!(route("caw://workflow/wf_abc%2F..").is_err());
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("wf abc"));
+        assert!(is_valid_id("wf_abc-123"));
+    }
+}
@@ -0,0 +1,86 @@
+use serde::Serialize;
+
+/// Longest `PATH` value returned before it is cut short.
+const MAX_PATH_CHARS: usize = 1024;
+
+#[derive(Debug, Serialize)]
+pub struct GitInfo {
+    pub found: bool,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvReport {
+    pub home: Option<String>,
+    /// True when `HOME` is unset and `~/.caw` resolves under `/tmp` instead.
+    pub home_fallback: bool,
+    pub path: String,
+    pub path_truncated: bool,
+    pub git: GitInfo,
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// `CAW_*` variables that are set, with secret-looking values redacted.
+    pub caw_vars: serde_json::Map<String, serde_json::Value>,
+    /// Desktop launch flags and what each one does.
+    pub flags: serde_json::Value,
+}
+
+/// Whether a variable or flag name suggests its value is a credential.
+pub fn looks_secret(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["TOKEN", "KEY", "SECRET", "PASSWORD", "AUTH"]
+        .iter()
+        .any(|word| name.contains(word))
+}
+
+fn git_info() -> GitInfo {
+    match std::process::Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => GitInfo {
+            found: true,
+            version: Some(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+        },
+        _ => GitInfo {
+            found: false,
+            version: None,
+        },
+    }
+}
+
+/// Snapshot the environment the backend sees. Read-only.
+pub fn report() -> EnvReport {
+    let home = std::env::var("HOME").ok().filter(|home| !home.is_empty());
+    let path = std::env::var("PATH").unwrap_or_default();
+    let path_truncated = path.chars().count() > MAX_PATH_CHARS;
+    let path = if path_truncated {
+        format!("{}…", path.chars().take(MAX_PATH_CHARS).collect::<String>())
+    } else {
+        path
+    };
+
+    let caw_vars = std::env::vars()
+        .filter(|(name, _)| name.starts_with("CAW_"))
+        .map(|(name, value)| {
+            let value = if looks_secret(&name) {
+                "[redacted]".to_string()
+            } else {
+                value
+            };
+            (name, serde_json::Value::String(value))
+        })
+        .collect();
+
+    EnvReport {
+        home_fallback: home.is_none(),
+        home,
+        path,
+        path_truncated,
+        git: git_info(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        caw_vars,
+        flags: serde_json::json!({
+            "CAW_SAFE_MODE": "skip the sidecar, saved settings, and window customization",
+            "CAW_NO_VIBRANCY": "solid window background and standard traffic lights (macOS)",
+        }),
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Per-request ceiling for the stream. The shared client's default timeout
+/// would cut it off after seconds; at this limit the stream just reconnects.
+pub const STREAM_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+/// First reconnect delay after the stream drops; doubles up to the maximum.
+pub const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ServerEvent {
+    /// The frame's `event:` field, `"message"` when absent.
+    pub event: String,
+    /// Parsed JSON when `data` is JSON, otherwise the raw text.
+    pub data: serde_json::Value,
+    pub id: Option<String>,
+}
+
+/// Incremental parser for `text/event-stream` bodies.
+#[derive(Default)]
+pub struct SseParser {
+    buf: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    /// Feed a chunk of the body and return the events it completed. Lines are
+    /// decoded only once complete, so split UTF-8 sequences survive.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<ServerEvent> {
+        self.buf.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                events.extend(self.dispatch());
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                "id" => self.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+
+    fn dispatch(&mut self) -> Option<ServerEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        let text = std::mem::take(&mut self.data).join("\n");
+        let data = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+        Some(ServerEvent {
+            event: event.unwrap_or_else(|| "message".to_string()),
+            data,
+            id: self.id.clone(),
+        })
+    }
+}
+
+/// Open the event stream at `url`, failing if the server doesn't serve one.
+pub async fn connect(client: &reqwest::Client, url: &str) -> Result<reqwest::Response, String> {
+    let resp = client
+        .get(url)
+        .header(reqwest::header::ACCEPT, "text/event-stream")
+        .timeout(STREAM_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    match resp.status() {
+        status if status.is_success() => Ok(resp),
+        reqwest::StatusCode::NOT_FOUND => {
+            Err("The caw server does not expose an /api/events stream".to_string())
+        }
+        status => Err(format!("Event stream failed with HTTP {status}")),
+    }
+}
+
+/// Read `resp` to the end, calling `on_event` for each complete event.
+pub async fn pump(
+    mut resp: reqwest::Response,
+    mut on_event: impl FnMut(ServerEvent),
+) -> Result<(), String> {
+    let mut parser = SseParser::default();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        for event in parser.push(&chunk) {
+            on_event(event);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_named_events_with_json_data() {
+        let mut parser = SseParser::default();
+        let events = parser.push(b"event: workflow.updated\nid: 7\ndata: {\"id\":\"wf_1\"}\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "workflow.updated");
+        assert_eq!(events[0].data, serde_json::json!({ "id": "wf_1" }));
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn joins_data_lines_and_defaults_the_event_name() {
+        let mut parser = SseParser::default();
+        let events = parser.push(b"data: first\r\ndata: second\r\n\r\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "message");
+        assert_eq!(events[0].data, serde_json::json!("first\nsecond"));
+        assert_eq!(events[0].id, None);
+    }
+
+    #[test]
+    fn skips_comments_and_frames_without_data() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b": keep-alive\n\nevent: ping\n\n").is_empty());
+        // The dropped frame's name doesn't leak into the next one
+        let events = parser.push(b"data: x\n\n");
+        assert_eq!(events[0].event, "message");
+    }
+
+    #[test]
+    fn reassembles_lines_split_across_chunks() {
+        let mut parser = SseParser::default();
+        let body = "data: caf\u{e9}\n\n".as_bytes();
+        let (head, tail) = body.split_at(10);
+        assert!(parser.push(head).is_empty());
+        let events = parser.push(tail);
+        assert_eq!(events[0].data, serde_json::json!("caf\u{e9}"));
+    }
+
+    #[test]
+    fn keeps_the_last_event_id_for_later_events() {
+        let mut parser = SseParser::default();
+        let events = parser.push(b"id: 3\ndata: a\n\ndata: b\n\n");
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].id.as_deref(), Some("3"));
+    }
+}
@@ -0,0 +1,22 @@
+use std::time::Duration;
+
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Orders proxied requests against sidecar restarts: requests hold the read
+/// side while in flight, and a restart takes the write side so it waits for
+/// them to drain and holds new ones back until the new sidecar is up.
+#[derive(Default)]
+pub struct RequestGate(RwLock<()>);
+
+impl RequestGate {
+    /// Wait out any restart in progress, then hold the gate open for one request.
+    pub async fn enter(&self) -> RwLockReadGuard<'_, ()> {
+        self.0.read().await
+    }
+
+    /// Wait up to `timeout` for in-flight requests to finish, then hold new
+    /// ones until the guard is dropped. `None` if they didn't drain in time.
+    pub async fn close(&self, timeout: Duration) -> Option<RwLockWriteGuard<'_, ()>> {
+        tokio::time::timeout(timeout, self.0.write()).await.ok()
+    }
+}
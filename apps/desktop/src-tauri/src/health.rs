@@ -0,0 +1,206 @@
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Longest readiness budget a caller may ask for.
+pub const MAX_WAIT_LIMIT: Duration = Duration::from_secs(300);
+
+/// Pacing and total budget for sidecar `/health` readiness polling.
+#[derive(Clone, Copy, Debug)]
+pub struct HealthConfig {
+    /// Total time to keep polling before giving up.
+    pub max_wait: Duration,
+    /// Delay after the first failed poll; doubles on each subsequent attempt.
+    pub initial_interval: Duration,
+    /// Upper bound for the backoff delay.
+    pub max_interval: Duration,
+    /// Timeout applied to each individual `/health` request.
+    pub request_timeout: Duration,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            max_wait: Duration::from_secs(30),
+            initial_interval: Duration::from_millis(100),
+            max_interval: Duration::from_secs(1),
+            request_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Bounds for the `startup_timeout_secs` setting, so a bad value can't make
+/// startup or a restart wait indefinitely.
+pub const STARTUP_TIMEOUT_MIN_SECS: u64 = 5;
+pub const STARTUP_TIMEOUT_MAX_SECS: u64 = 120;
+
+impl HealthConfig {
+    /// Budget for a sidecar start or restart: `secs` from settings, clamped
+    /// to the allowed range.
+    pub fn startup(secs: u64) -> Self {
+        let secs = secs.clamp(STARTUP_TIMEOUT_MIN_SECS, STARTUP_TIMEOUT_MAX_SECS);
+        Self {
+            max_wait: Duration::from_secs(secs),
+            ..Self::default()
+        }
+    }
+}
+
+/// Apply ±20% random jitter so polls don't lock-step with the sidecar's startup work.
+fn jittered(interval: Duration) -> Duration {
+    interval.mul_f64(0.8 + fastrand::f64() * 0.4)
+}
+
+/// Why the sidecar never became healthy, based on the last failed poll.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthFailure {
+    /// Connections were refused: nothing is listening on the port.
+    NotListening,
+    /// Connections were accepted but `/health` never returned 200 in time.
+    NotReady,
+}
+
+impl HealthFailure {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Self::NotListening => {
+                "nothing is listening on the port (the sidecar may have exited or failed to bind)"
+            }
+            Self::NotReady => {
+                "the port accepted connections but /health never succeeded \
+                 (a stuck migration, or another process owns the port)"
+            }
+        }
+    }
+}
+
+/// Poll `url` until it returns a success status or the budget in `config` runs out.
+/// Early polls are fast to catch quick starts; later ones back off exponentially.
+pub async fn poll_health_until_ready(
+    client: &reqwest::Client,
+    url: &str,
+    config: &HealthConfig,
+) -> Result<(), HealthFailure> {
+    let deadline = Instant::now() + config.max_wait;
+    let mut interval = config.initial_interval;
+
+    loop {
+        let failure = match client.get(url).timeout(config.request_timeout).send().await {
+            Ok(resp) if resp.status().is_success() => return Ok(()),
+            Ok(_) => HealthFailure::NotReady,
+            Err(e) if e.is_connect() && !e.is_timeout() => HealthFailure::NotListening,
+            Err(_) => HealthFailure::NotReady,
+        };
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(failure);
+        }
+
+        let delay = jittered(interval).min(deadline - now);
+        tokio::time::sleep(delay).await;
+        interval = (interval * 2).min(config.max_interval);
+    }
+}
+
+/// How often the background monitor polls `/health` while a window is focused.
+pub const MONITOR_INTERVAL: Duration = Duration::from_secs(10);
+/// Slower pace while the app is in the background.
+pub const MONITOR_INTERVAL_BLURRED: Duration = Duration::from_secs(30);
+
+/// Samples kept for `health_history`.
+const MAX_HEALTH_SAMPLES: usize = 300;
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct HealthSample {
+    /// Unix timestamp (milliseconds).
+    pub timestamp: u64,
+    pub ok: bool,
+    /// The request timed out, as opposed to being refused or erroring.
+    pub timed_out: bool,
+    pub latency_ms: u64,
+}
+
+/// Recent health poll results. The monitor loop is the only writer.
+#[derive(Default)]
+pub struct HealthHistory(std::sync::Mutex<std::collections::VecDeque<HealthSample>>);
+
+impl HealthHistory {
+    pub fn samples(&self) -> Vec<HealthSample> {
+        self.0
+            .lock()
+            .map(|samples| samples.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, sample: HealthSample) {
+        if let Ok(mut samples) = self.0.lock() {
+            if samples.len() == MAX_HEALTH_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(sample);
+        }
+    }
+}
+
+/// Poll `url` once, record whether it succeeded and how long it took, and
+/// return the sample.
+pub async fn sample_health(
+    client: &reqwest::Client,
+    url: &str,
+    history: &HealthHistory,
+) -> HealthSample {
+    let start = Instant::now();
+    let result = client
+        .get(url)
+        .timeout(HealthConfig::default().request_timeout)
+        .send()
+        .await;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let sample = HealthSample {
+        timestamp,
+        ok: result.as_ref().is_ok_and(|resp| resp.status().is_success()),
+        timed_out: result.as_ref().is_err_and(|e| e.is_timeout()),
+        latency_ms: start.elapsed().as_millis() as u64,
+    };
+    history.record(sample);
+    sample
+}
+
+/// Counts consecutive `/health` timeouts to spot a sidecar that still accepts
+/// connections but never answers (e.g. deadlocked).
+#[derive(Default)]
+pub struct HangDetector {
+    streak: u32,
+    since: Option<Instant>,
+}
+
+impl HangDetector {
+    /// Feed one sample. Returns true once `threshold` consecutive timeouts
+    /// have landed within `window`; anything other than a timeout resets the
+    /// streak. A threshold of 0 disables detection.
+    pub fn observe(&mut self, sample: &HealthSample, threshold: u32, window: Duration) -> bool {
+        if !sample.timed_out || threshold == 0 {
+            self.reset();
+            return false;
+        }
+        let now = Instant::now();
+        match self.since {
+            Some(since) if now - since <= window => self.streak += 1,
+            _ => {
+                self.since = Some(now);
+                self.streak = 1;
+            }
+        }
+        self.streak >= threshold
+    }
+
+    pub fn reset(&mut self) {
+        self.streak = 0;
+        self.since = None;
+    }
+}
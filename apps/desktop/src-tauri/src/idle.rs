@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often the idle timer checks whether the sidecar should be stopped.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Last user or API activity, and whether the sidecar is stopped for idling.
+pub struct Activity {
+    last: Mutex<Instant>,
+    idle_stopped: AtomicBool,
+    /// Serializes wake-ups so concurrent requests restart the sidecar once.
+    pub wake: tokio::sync::Mutex<()>,
+}
+
+impl Activity {
+    pub fn new() -> Self {
+        Self {
+            last: Mutex::new(Instant::now()),
+            idle_stopped: AtomicBool::new(false),
+            wake: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn touch(&self) {
+        if let Ok(mut last) = self.last.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        self.last
+            .lock()
+            .map(|last| last.elapsed())
+            .unwrap_or_default()
+    }
+
+    pub fn is_idle_stopped(&self) -> bool {
+        self.idle_stopped.load(Ordering::SeqCst)
+    }
+
+    pub fn set_idle_stopped(&self, stopped: bool) {
+        self.idle_stopped.store(stopped, Ordering::SeqCst);
+    }
+}
+
+/// Idle threshold from the `idle_stop_minutes` setting; `None` when disabled.
+pub fn threshold(minutes: u32) -> Option<Duration> {
+    (minutes > 0).then(|| Duration::from_secs(u64::from(minutes) * 60))
+}
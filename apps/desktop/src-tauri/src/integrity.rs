@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Hash of `binaries/caw-<target>` recorded by `build.rs`.
+const EXPECTED_SHA256: &str = env!("CAW_SIDECAR_SHA256");
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    /// `None` when the build had no sidecar binary to hash.
+    pub expected: Option<String>,
+    pub actual: String,
+    pub path: String,
+}
+
+/// Where Tauri places the bundled sidecar: next to the app executable.
+pub fn sidecar_path() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let dir = exe
+        .parent()
+        .ok_or_else(|| "Cannot locate the app directory".to_string())?;
+    let name = if cfg!(windows) { "caw.exe" } else { "caw" };
+    Ok(dir.join(name))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Hash the resolved sidecar binary and compare it with the build-time hash.
+pub fn verify() -> Result<IntegrityReport, String> {
+    let path = sidecar_path()?;
+    let bytes =
+        std::fs::read(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let actual = sha256_hex(&bytes);
+    let expected = (!EXPECTED_SHA256.is_empty()).then(|| EXPECTED_SHA256.to_string());
+    Ok(IntegrityReport {
+        ok: expected.as_deref() == Some(actual.as_str()),
+        expected,
+        actual,
+        path: path.to_string_lossy().into_owned(),
+    })
+}
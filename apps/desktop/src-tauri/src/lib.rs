@@ -1,31 +1,627 @@
-use tauri::menu::{MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use tauri::menu::{
+    CheckMenuItem, CheckMenuItemBuilder, MenuBuilder, MenuItem, MenuItemBuilder,
+    PredefinedMenuItem, Submenu, SubmenuBuilder,
+};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
 use tauri::webview::WebviewWindowBuilder;
 use tauri::WebviewUrl;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_opener::OpenerExt;
+use tauri_plugin_shell::process::{CommandChild, CommandEvent, TerminatedPayload};
 use tauri_plugin_shell::ShellExt;
-use window_vibrancy::{apply_vibrancy, NSVisualEffectMaterial};
+use tauri_plugin_updater::UpdaterExt;
+use tracing::Instrument;
 
-struct SidecarState(std::sync::Mutex<Option<tauri_plugin_shell::process::CommandChild>>);
+mod api;
+mod app_nap;
+mod appearance;
+mod batch;
+mod bench;
+mod capabilities;
+mod db;
+mod dbwatch;
+mod deeplink;
+mod environment;
+mod events;
+mod gate;
+mod health;
+mod idle;
+mod integrity;
+mod logs;
+mod migrate;
+mod notify;
+mod process;
+mod procs;
+mod project;
+mod server;
+mod settings;
+mod startup;
+mod stats;
+mod supervision;
+mod terminal;
 
-/// Resolve the database path for the sidecar.
-/// 1. Try `git rev-parse --show-toplevel` → `<repo_root>/.caw/workflows.db`
-/// 2. Fall back to `~/.caw/workflows.db` (global mode)
-fn resolve_db_path() -> String {
-    if let Ok(output) = std::process::Command::new("git")
-        .args(["rev-parse", "--show-toplevel"])
-        .output()
-    {
-        if output.status.success() {
-            let repo_root = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !repo_root.is_empty() {
-                return format!("{repo_root}/.caw/workflows.db");
+use api::ApiResponse;
+use batch::{BatchOp, BatchResult};
+use capabilities::Capabilities;
+use gate::RequestGate;
+use db::{resolve_db_path, DbMode, LockRepair, ResolvedDb};
+use health::{
+    poll_health_until_ready, HangDetector, HealthConfig, HealthFailure, HealthHistory,
+};
+use idle::Activity;
+use logs::desktop_log;
+use process::{RestartFailure, RestartStage, Sidecar, StopOutcome};
+use server::ServerConfig;
+use settings::{ActivationPolicy, ChromeMode, DesktopSettings, Theme, TrafficLights};
+use startup::{timed, StartupMetrics, StartupTimings};
+use stats::SizeHistory;
+use supervision::Supervision;
+
+/// Launched with `CAW_SAFE_MODE=1`: no sidecar, no persisted settings, no
+/// native window tweaks — a way back in when a setting breaks startup.
+struct SafeMode(bool);
+
+fn safe_mode_requested() -> bool {
+    std::env::var("CAW_SAFE_MODE").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Default timeout for requests to the sidecar.
+const HTTP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+struct SidecarState {
+    /// The running sidecar, spawned or adopted; `None` while stopped.
+    sidecar: Mutex<Option<Sidecar>>,
+    /// Database the sidecar is (or will be) running against.
+    db: Mutex<ResolvedDb>,
+    /// Database the current sidecar was actually spawned with.
+    spawned_db: Mutex<Option<String>>,
+    server: Mutex<ServerConfig>,
+    /// Set on exit so no restart path spawns a sidecar that would outlive the app.
+    shutting_down: AtomicBool,
+    /// Set once a sidecar has been spawned, so `server_port` can tell "never
+    /// started" (e.g. safe mode) apart from a port in use.
+    started: AtomicBool,
+    /// Capabilities reported by the sidecar, keyed by the PID they came from.
+    capabilities: Mutex<Option<(u32, Capabilities)>>,
+    /// Recent stderr of the current sidecar; a fast failure's reason ends up here.
+    stderr_tail: logs::Tail,
+    /// Recent stdout and stderr lines across sidecar restarts, for `server_logs`.
+    output_tail: logs::Tail,
+    /// Exit details and final stderr of the last unexpected exit.
+    last_crash: Mutex<Option<serde_json::Value>>,
+    /// Recent automatic restarts after crashes; reset by `restart_server`.
+    crash_restarts: process::CrashRestarts,
+    /// Held for the duration of any restart.
+    restarting: process::RestartFlag,
+    /// Why the last spawn attempt failed (e.g. a missing or non-executable
+    /// binary); cleared by the next successful spawn.
+    spawn_error: Mutex<Option<String>>,
+}
+
+/// Stderr lines kept for crash and timeout reports.
+const STDERR_TAIL_LINES: usize = 20;
+/// Output lines kept for `server_logs`.
+const OUTPUT_TAIL_LINES: usize = 500;
+
+impl SidecarState {
+    fn db_path(&self) -> Result<String, String> {
+        Ok(self.db.lock().map_err(|e| e.to_string())?.path.clone())
+    }
+
+    fn server(&self) -> ServerConfig {
+        match self.server.lock() {
+            Ok(config) => config.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// PID of the running sidecar, whether spawned or adopted.
+    fn pid(&self) -> Option<u32> {
+        self.sidecar.lock().ok()?.as_ref().map(Sidecar::pid)
+    }
+
+    fn is_running(&self) -> bool {
+        self.pid().is_some()
+    }
+}
+
+struct SettingsState(Mutex<DesktopSettings>);
+
+/// One HTTP client shared by every command so requests to the sidecar reuse
+/// keep-alive connections. Calls that need a different timeout set it per request.
+struct HttpClient(Mutex<reqwest::Client>);
+
+fn http_client(app: &tauri::AppHandle) -> reqwest::Client {
+    match app.state::<HttpClient>().0.lock() {
+        Ok(client) => client.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Build the sidecar client. Proxies are bypassed unless asked for, since a
+/// system `HTTP_PROXY`/`ALL_PROXY` can capture loopback traffic.
+fn build_http_client(use_system_proxy: bool) -> reqwest::Result<reqwest::Client> {
+    let builder = reqwest::Client::builder().timeout(HTTP_TIMEOUT);
+    if use_system_proxy {
+        builder.build()
+    } else {
+        builder.no_proxy().build()
+    }
+}
+
+/// Hold proxied requests back for a sidecar restart, after giving in-flight
+/// ones up to `request_drain_timeout_secs` to finish. Keep the guard until the
+/// restart is done. Fails if they don't finish in time, so a restart never
+/// runs under requests it would cut off.
+async fn drain_requests(
+    app: &tauri::AppHandle,
+) -> Result<tokio::sync::RwLockWriteGuard<'_, ()>, String> {
+    let secs = app
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .map(|s| s.request_drain_timeout_secs)
+        .unwrap_or_default();
+    let timeout = std::time::Duration::from_secs(secs);
+    app.state::<RequestGate>().inner().close(timeout).await.ok_or_else(|| {
+        desktop_log!("Requests still in flight after {secs}s; not restarting");
+        format!("Requests are still in flight after {secs}s; try again once they finish")
+    })
+}
+
+/// Last sidecar liveness told to the frontend; `None` until the first report.
+struct Liveness(Mutex<Option<bool>>);
+
+/// Record whether the sidecar is up, emitting `server://status-changed` only
+/// when that differs from the last report.
+fn report_liveness(app: &tauri::AppHandle, running: bool) {
+    let Some(liveness) = app.try_state::<Liveness>() else {
+        return;
+    };
+    let changed = liveness
+        .0
+        .lock()
+        .is_ok_and(|mut last| last.replace(running) != Some(running));
+    if changed {
+        let _ = app.emit("server://status-changed", serde_json::json!({ "running": running }));
+        if let Some(tray) = app.try_state::<Tray>() {
+            tray.show_status(running);
+        }
+    }
+}
+
+/// Long-running background tasks, aborted when the app exits.
+struct BackgroundTasks(Mutex<Vec<tauri::async_runtime::JoinHandle<()>>>);
+
+/// The `subscribe_events` stream task, if one is running.
+struct EventStream(Mutex<Option<tauri::async_runtime::JoinHandle<()>>>);
+
+/// The project `.caw/config.toml` loaded at launch, and where the effective
+/// port and transport came from.
+struct ProjectState {
+    loaded: Option<project::LoadedProject>,
+    port_source: Mutex<project::Source>,
+    transport_source: Mutex<project::Source>,
+}
+
+/// When `reresolve_db` last ran; the lock also serializes overlapping calls.
+struct Reresolve(tokio::sync::Mutex<Option<std::time::Instant>>);
+
+/// Calls to `reresolve_db` this soon after the last one reuse its result.
+const RERESOLVE_DEBOUNCE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Workspace context captured at launch.
+struct WorkspaceState {
+    /// Git root the app was launched in, if any.
+    git_root: Option<String>,
+}
+
+/// Warn (without blocking the launch) when the sidecar binary doesn't match
+/// the hash recorded at build time.
+fn warn_if_sidecar_modified(app: &tauri::AppHandle) {
+    match integrity::verify() {
+        Ok(report) if report.expected.is_some() && !report.ok => {
+            desktop_log!(
+                "Warning: sidecar hash mismatch at {} (expected {:?}, got {})",
+                report.path,
+                report.expected,
+                report.actual
+            );
+            let _ = app.emit("sidecar://integrity-warning", &report);
+        }
+        Ok(_) => {}
+        Err(e) => desktop_log!("Warning: could not verify sidecar integrity: {e}"),
+    }
+}
+
+fn spawn_sidecar(app: &tauri::AppHandle, db_path: &str) -> Result<CommandChild, String> {
+    let args = app.state::<SidecarState>().server().sidecar_args(db_path);
+    let spawned = app
+        .shell()
+        .sidecar("caw")
+        .map_err(|e| format!("Sidecar binary unavailable: {e}"))
+        .and_then(|sidecar| {
+            sidecar
+                .args(args)
+                .spawn()
+                .map_err(|e| format!("Failed to spawn sidecar: {e}"))
+        });
+    let state = app.state::<SidecarState>();
+    let (mut rx, child) = match spawned {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            desktop_log!("Error: {e}");
+            if let Ok(mut spawn_error) = state.spawn_error.lock() {
+                *spawn_error = Some(e.clone());
+            }
+            let _ = app.emit("sidecar://spawn-failed", serde_json::json!({ "error": e }));
+            return Err(e);
+        }
+    };
+    if let Ok(mut spawn_error) = state.spawn_error.lock() {
+        *spawn_error = None;
+    }
+    track_sidecar(app, db_path, child.pid());
+
+    // Record output to sidecar.log and watch for the process exiting on its own
+    state.stderr_tail.clear();
+    let pid = child.pid();
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut log = logs::Appender::open(logs::SIDECAR_LOG).ok();
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => {
+                    let line = String::from_utf8_lossy(&line);
+                    let line = line.trim_end();
+                    if let Some(log) = log.as_mut() {
+                        log.write_line(line);
+                    }
+                    handle.state::<SidecarState>().output_tail.push(line);
+                }
+                CommandEvent::Stderr(line) => {
+                    let line = String::from_utf8_lossy(&line);
+                    let line = line.trim_end();
+                    if let Some(log) = log.as_mut() {
+                        log.write_line(line);
+                    }
+                    let state = handle.state::<SidecarState>();
+                    state.stderr_tail.push(line);
+                    state.output_tail.push(line);
+                }
+                CommandEvent::Terminated(payload) => {
+                    on_sidecar_terminated(&handle, pid, payload).await;
+                    break;
+                }
+                _ => {}
             }
         }
+    });
+
+    Ok(child)
+}
+
+/// Record that a sidecar (spawned or adopted) now serves `db_path`: its PID
+/// file, the database watcher, and the menu.
+fn track_sidecar(app: &tauri::AppHandle, db_path: &str, pid: u32) {
+    let state = app.state::<SidecarState>();
+    let prevent_app_nap = app
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .is_ok_and(|s| s.prevent_app_nap);
+    if prevent_app_nap {
+        app_nap::begin();
+    }
+    state.started.store(true, Ordering::SeqCst);
+    if let Ok(mut spawned_db) = state.spawned_db.lock() {
+        if let Some(previous) = spawned_db.replace(db_path.to_string()) {
+            process::remove_pid_file(&previous);
+        }
+    }
+    process::write_pid_file(db_path, pid);
+    watch_db(app, db_path);
+    if let Some(item) = app.try_state::<DbMenuItem>() {
+        let _ = item.0.set_text(db_menu_label(db_path));
+    }
+    remember_db(app, db_path);
+}
+
+/// Handle the sidecar process exiting. Intentional stops take the child out of
+/// `SidecarState` first, so only an exit of the still-managed child is a crash.
+async fn on_sidecar_terminated(app: &tauri::AppHandle, pid: u32, payload: TerminatedPayload) {
+    let crashed = {
+        let state = app.state::<SidecarState>();
+        let Ok(mut guard) = state.sidecar.lock() else {
+            return;
+        };
+        if guard.as_ref().map(Sidecar::pid) == Some(pid) {
+            guard.take();
+            app_nap::end();
+            true
+        } else {
+            false
+        }
+    };
+    if !crashed {
+        return;
+    }
+    report_liveness(app, false);
+
+    let auto_restart = app
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .map(|s| s.auto_restart_on_crash)
+        .unwrap_or(true)
+        && !app.state::<Supervision>().is_paused();
+    desktop_log!(
+        "Warning: sidecar exited unexpectedly (code {:?}, signal {:?})",
+        payload.code, payload.signal
+    );
+    let state = app.state::<SidecarState>();
+    let delay = if auto_restart {
+        state.crash_restarts.next_delay()
+    } else {
+        None
+    };
+    let restart = delay.is_some();
+    let crash = serde_json::json!({
+        "code": payload.code,
+        "signal": payload.signal,
+        "stderr": state.stderr_tail.lines(),
+    });
+    if let Ok(mut last_crash) = state.last_crash.lock() {
+        *last_crash = Some(crash.clone());
+    }
+    let mut event = crash;
+    event["restarting"] = restart.into();
+    let _ = app.emit("sidecar://crashed", event);
+
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+        if let Err(e) = restart_sidecar(app).await {
+            desktop_log!("Warning: failed to restart crashed sidecar: {e}");
+        }
+    } else if auto_restart {
+        desktop_log!(
+            "Warning: sidecar crashed {} times within {}s; not restarting again",
+            process::CRASH_RESTART_LIMIT,
+            process::CRASH_RESTART_WINDOW.as_secs()
+        );
+        let _ = app.emit(
+            "sidecar://restart-limit",
+            serde_json::json!({
+                "attempts": state.crash_restarts.count(),
+                "window_secs": process::CRASH_RESTART_WINDOW.as_secs(),
+            }),
+        );
+    }
+}
+
+/// If the database directory isn't writable (read-only checkout, mounted DMG),
+/// switch to the global database so the sidecar can start, and tell the frontend.
+fn fall_back_if_readonly(app: &tauri::AppHandle, db: &mut ResolvedDb) {
+    let Err(reason) = db::probe_writable(&db.path) else {
+        return;
+    };
+    let global = db::global_db_path();
+    if db.path == global {
+        return;
+    }
+
+    desktop_log!("Warning: {reason}; falling back to {global}");
+    let _ = app.emit(
+        "db://readonly-fallback",
+        serde_json::json!({ "from": db.path, "to": global, "reason": reason }),
+    );
+    *db = ResolvedDb {
+        path: global,
+        mode: DbMode::Global,
+    };
+}
+
+/// Stop the running sidecar (if any) gracefully, re-spawn it against the
+/// active database, and wait for it to report healthy.
+async fn restart_sidecar(app: &tauri::AppHandle) -> Result<serde_json::Value, String> {
+    restart_sidecar_with(app, startup_health_config(app)).await
+}
+
+/// Health polling budget from the `startup_timeout_secs` setting.
+fn startup_health_config(app: &tauri::AppHandle) -> HealthConfig {
+    let secs = app
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .map(|s| s.startup_timeout_secs)
+        .unwrap_or(DesktopSettings::default().startup_timeout_secs);
+    HealthConfig::startup(secs)
+}
+
+/// [`restart_sidecar`] with an explicit health-check budget.
+async fn restart_sidecar_with(
+    app: &tauri::AppHandle,
+    config: HealthConfig,
+) -> Result<serde_json::Value, String> {
+    try_restart_sidecar(app, config).await.map_err(|failure| failure.message)
+}
+
+/// `restart_sidecar_with`, reporting which stage failed and on what port.
+async fn try_restart_sidecar(
+    app: &tauri::AppHandle,
+    config: HealthConfig,
+) -> Result<serde_json::Value, RestartFailure> {
+    let state = app.state::<SidecarState>();
+    let _restarting = state.restarting.claim().ok_or_else(|| restart_busy(app))?;
+    respawn_sidecar(app, config).await
+}
+
+fn restart_busy(app: &tauri::AppHandle) -> RestartFailure {
+    let port = app.state::<SidecarState>().server().port;
+    RestartFailure::new(RestartStage::Busy, "restart already in progress", port)
+}
+
+/// Stop, re-spawn, and health-check the sidecar. Callers hold the restart claim.
+async fn respawn_sidecar(
+    app: &tauri::AppHandle,
+    config: HealthConfig,
+) -> Result<serde_json::Value, RestartFailure> {
+    let state = app.state::<SidecarState>();
+    let port = state.server().port;
+    let spawn_failed = |message: String| RestartFailure::new(RestartStage::Spawn, message, port);
+
+    // SIGTERM first so the old sidecar can flush its WAL
+    let previous = state.sidecar.lock().map_err(|e| spawn_failed(e.to_string()))?.take();
+    if let Some(sidecar) = previous {
+        process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await;
+    }
+
+    // Small delay to let the port free up
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    // Re-spawn sidecar
+    let db_path = {
+        let mut db = state.db.lock().map_err(|e| spawn_failed(e.to_string()))?;
+        fall_back_if_readonly(app, &mut db);
+        db.path.clone()
+    };
+    {
+        // Hold the lock while spawning so the crash watcher sees the new child
+        let mut guard = state.sidecar.lock().map_err(|e| spawn_failed(e.to_string()))?;
+        if state.shutting_down.load(Ordering::SeqCst) {
+            return Err(spawn_failed("The app is shutting down".to_string()));
+        }
+        let child = spawn_sidecar(app, &db_path).map_err(spawn_failed)?;
+        *guard = Some(Sidecar::Child(child));
+    }
+
+    // Poll health until ready
+    let health_url = state.server().health_url();
+    match poll_health_until_ready(&http_client(app), &health_url, &config).await {
+        Ok(()) => {
+            report_liveness(app, true);
+            Ok(serde_json::json!({ "success": true }))
+        }
+        Err(failure) => Err(RestartFailure::new(
+            RestartStage::Health,
+            emit_health_timeout(app, &config, failure),
+            port,
+        )),
+    }
+}
+
+/// The watchdog saw repeated `/health` timeouts. If the process is still alive
+/// it is wedged rather than crashed, so force a restart.
+async fn restart_if_hung(
+    app: &tauri::AppHandle,
+    pid: u32,
+    timeouts: u32,
+    window: std::time::Duration,
+) {
+    if app.state::<Supervision>().is_paused() {
+        return;
+    }
+    if !process::is_alive(pid) {
+        return;
+    }
+    desktop_log!(
+        "Warning: sidecar (pid {pid}) timed out on /health {timeouts} times in {}s; restarting",
+        window.as_secs()
+    );
+    let _ = app.emit(
+        "sidecar://hung",
+        serde_json::json!({
+            "pid": pid,
+            "timeouts": timeouts,
+            "window_secs": window.as_secs(),
+        }),
+    );
+    if let Err(e) = restart_sidecar(app).await {
+        desktop_log!("Warning: failed to restart hung sidecar: {e}");
+    }
+}
+
+/// Tell the frontend the sidecar never became healthy, and why.
+/// Returns the human-readable message for use as a command error.
+fn emit_health_timeout(
+    app: &tauri::AppHandle,
+    config: &HealthConfig,
+    failure: HealthFailure,
+) -> String {
+    let message = format!(
+        "Server did not become healthy within {} seconds: {}",
+        config.max_wait.as_secs(),
+        failure.describe()
+    );
+    let stderr = app.state::<SidecarState>().stderr_tail.lines();
+    let _ = app.emit(
+        "sidecar://timeout",
+        serde_json::json!({ "reason": failure, "message": message, "stderr": stderr }),
+    );
+    message
+}
+
+/// Ask the sidecar to flush its WAL into the main db file before we kill it.
+/// Runs on the main loop during exit, so it uses a blocking client with a tight
+/// timeout; any failure is logged and shutdown proceeds regardless.
+fn checkpoint_before_exit(server: &ServerConfig, caps: Option<&Capabilities>) {
+    if caps.and_then(|caps| caps.supports(capabilities::CHECKPOINT)) == Some(false) {
+        return;
+    }
+    let client = match reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(1))
+        .no_proxy()
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            desktop_log!("Warning: could not build checkpoint client: {e}");
+            return;
+        }
+    };
+
+    match client.post(server.url("/api/admin/checkpoint")).send() {
+        Ok(resp) if resp.status().is_success() => desktop_log!("Sidecar checkpoint complete"),
+        Ok(resp) => desktop_log!("Warning: sidecar checkpoint returned {}", resp.status()),
+        Err(e) => desktop_log!("Warning: sidecar checkpoint failed: {e}"),
     }
+}
 
-    // Fall back to global ~/.caw/workflows.db
-    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-    format!("{home}/.caw/workflows.db")
+/// Traffic light inset in minimal chrome mode.
+#[cfg(target_os = "macos")]
+const TRAFFIC_LIGHTS_MINIMAL: TrafficLights = TrafficLights { x: 10.0, y: 12.0 };
+
+/// Show or hide the window's menu bar (Windows and Linux; macOS keeps its
+/// global menu so shortcuts keep working) and place the traffic lights at
+/// `lights`, or tighter in minimal mode.
+fn apply_chrome_mode<R: tauri::Runtime>(
+    window: &tauri::WebviewWindow<R>,
+    mode: ChromeMode,
+    lights: TrafficLights,
+) {
+    #[cfg(target_os = "macos")]
+    if !appearance::vibrancy_disabled() {
+        let lights = match mode {
+            ChromeMode::Normal => lights,
+            ChromeMode::Minimal => TRAFFIC_LIGHTS_MINIMAL,
+        };
+        set_traffic_light_position(window, lights.x, lights.y);
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = lights;
+        let _ = match mode {
+            ChromeMode::Normal => window.show_menu(),
+            ChromeMode::Minimal => window.hide_menu(),
+        };
+    }
 }
 
 #[cfg(target_os = "macos")]
@@ -78,124 +674,2333 @@ fn set_traffic_light_position<R: tauri::Runtime>(window: &tauri::WebviewWindow<R
     }
 }
 
+/// Allowed range for the `status_timeout_ms` setting.
+const STATUS_TIMEOUT_MIN_MS: u64 = 100;
+const STATUS_TIMEOUT_MAX_MS: u64 = 10_000;
+
+/// A `/health` request on the shared client with the short status timeout,
+/// for status queries rather than startup waits.
+fn status_request(app: &tauri::AppHandle) -> reqwest::RequestBuilder {
+    let ms = app
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .map(|s| s.status_timeout_ms)
+        .unwrap_or(DesktopSettings::default().status_timeout_ms)
+        .clamp(STATUS_TIMEOUT_MIN_MS, STATUS_TIMEOUT_MAX_MS);
+    let health_url = app.state::<SidecarState>().server().health_url();
+    http_client(app)
+        .get(health_url)
+        .timeout(std::time::Duration::from_millis(ms))
+}
+
+/// Timeout for status checks (`server_status` and friends), clamped to
+/// 100–10000ms. Returns the value saved.
+#[tauri::command]
+fn set_status_timeout(app: tauri::AppHandle, ms: u64) -> Result<u64, String> {
+    let ms = ms.clamp(STATUS_TIMEOUT_MIN_MS, STATUS_TIMEOUT_MAX_MS);
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.status_timeout_ms = ms;
+    settings::save(&settings)?;
+    Ok(ms)
+}
+
+/// One-off health check, e.g. for the frontend's initial state; after that,
+/// `server://status-changed` reports transitions as they happen.
+#[tauri::command]
+async fn server_status(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let running = status_request(&app)
+        .send()
+        .await
+        .is_ok_and(|resp| resp.status().is_success());
+    let paused = app.state::<Supervision>().remaining();
+    let spawn_error = app.state::<SidecarState>().spawn_error.lock().map(|e| e.clone());
+    let spawn_error = spawn_error.map_err(|e| e.to_string())?;
+    Ok(serde_json::json!({
+        "running": running,
+        "spawn_error": spawn_error,
+        "supervision_paused": paused.is_some(),
+        "supervision_resumes_in_secs": paused.map(|left| left.as_secs()),
+    }))
+}
+
+/// The sidecar's own `/health` report (version, uptime, db path, migrations)
+/// as it sent it, for diagnostics. `{ reachable: false }` when it doesn't answer.
+#[tauri::command]
+async fn server_health_detail(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let resp = status_request(&app).send().await;
+    let resp = match resp {
+        Ok(resp) => resp,
+        Err(e) => return Ok(serde_json::json!({ "reachable": false, "error": e.to_string() })),
+    };
+    let status = resp.status().as_u16();
+    let text = resp.text().await.map_err(|e| e.to_string())?;
+    let health = serde_json::from_str(&text).unwrap_or(serde_json::Value::String(text));
+    Ok(serde_json::json!({ "reachable": true, "status": status, "health": health }))
+}
+
+/// Compare the sidecar's reported version with the app's and emit
+/// `sidecar://version-mismatch` if they differ. Only informs; a sidecar
+/// that doesn't report a version is assumed to match.
+async fn check_sidecar_version(app: &tauri::AppHandle) {
+    let Ok(caps) = sidecar_capabilities(app).await else {
+        return;
+    };
+    let Some(sidecar) = caps.version.as_deref() else {
+        return;
+    };
+    let desktop = env!("CARGO_PKG_VERSION");
+    if sidecar != desktop {
+        desktop_log!("Warning: sidecar version {sidecar} differs from app version {desktop}");
+        let _ = app.emit(
+            "sidecar://version-mismatch",
+            serde_json::json!({ "desktop": desktop, "sidecar": sidecar }),
+        );
+    }
+}
+
+/// Stand down the health monitor, hang watchdog, and crash auto-restart for
+/// `minutes` (default and maximum 10), e.g. while running a migration or
+/// backup from the CLI. Supervision resumes on its own when the time is up.
+#[tauri::command]
+fn pause_supervision(app: tauri::AppHandle, minutes: Option<u64>) -> serde_json::Value {
+    let requested = minutes.map_or(supervision::MAX_PAUSE, |m| {
+        std::time::Duration::from_secs(m.saturating_mul(60))
+    });
+    let paused = app.state::<Supervision>().pause(requested);
+    desktop_log!("Sidecar supervision paused for {}s", paused.as_secs());
+    let event = serde_json::json!({ "paused": true, "resumes_in_secs": paused.as_secs() });
+    let _ = app.emit("sidecar://supervision", &event);
+    event
+}
+
+/// Resume supervision early. Returns false if it wasn't paused.
+#[tauri::command]
+fn resume_supervision(app: tauri::AppHandle) -> bool {
+    let resumed = app.state::<Supervision>().resume();
+    if resumed {
+        desktop_log!("Sidecar supervision resumed");
+        let _ = app.emit("sidecar://supervision", serde_json::json!({ "paused": false }));
+    }
+    resumed
+}
+
+/// The port the sidecar was started on, so the frontend never has to assume
+/// 3100. Errors if no sidecar has been started yet.
+#[tauri::command]
+async fn server_port(app: tauri::AppHandle) -> Result<u16, String> {
+    let state = app.state::<SidecarState>();
+    if !state.started.load(Ordering::SeqCst) {
+        return Err("The server has not been started".to_string());
+    }
+    Ok(state.server().port)
+}
+
+/// Restart the sidecar and wait up to `timeout_secs` (default: the
+/// `startup_timeout_secs` setting) for it to become healthy, e.g. longer on a
+/// slow machine or a large database. On failure the error says whether
+/// spawning or the health check failed.
+#[tauri::command]
+async fn restart_server(
+    app: tauri::AppHandle,
+    timeout_secs: Option<u64>,
+) -> Result<serde_json::Value, RestartFailure> {
+    // Claim before draining, so a second click returns at once instead of queueing
+    let state = app.state::<SidecarState>();
+    let _restarting = state.restarting.claim().ok_or_else(|| restart_busy(&app))?;
+    let mut config = startup_health_config(&app);
+    if let Some(secs) = timeout_secs {
+        if !(1..=health::MAX_WAIT_LIMIT.as_secs()).contains(&secs) {
+            let message = format!(
+                "timeout_secs must be between 1 and {}",
+                health::MAX_WAIT_LIMIT.as_secs()
+            );
+            let port = state.server().port;
+            return Err(RestartFailure::new(RestartStage::Config, message, port));
+        }
+        config.max_wait = std::time::Duration::from_secs(secs);
+    }
+    let _drain = drain_requests(&app).await?;
+    state.crash_restarts.reset();
+    respawn_sidecar(&app, config).await
+}
+
+/// Restart every managed sidecar, returning one result per database. The app
+/// runs a single sidecar today, so this is `restart_server` with a uniform shape.
+#[tauri::command]
+async fn restart_all_servers(app: tauri::AppHandle) -> Result<Vec<serde_json::Value>, String> {
+    let db_path = app.state::<SidecarState>().db_path()?;
+    let _drain = drain_requests(&app).await?;
+    app.state::<SidecarState>().crash_restarts.reset();
+    let result = match restart_sidecar(&app).await {
+        Ok(_) => serde_json::json!({ "db_path": db_path, "ok": true, "error": null }),
+        Err(e) => serde_json::json!({ "db_path": db_path, "ok": false, "error": e }),
+    };
+    Ok(vec![result])
+}
+
+/// Fire a cheap query right after the sidecar is healthy so its SQLite page
+/// cache is primed before the user's first real request.
+async fn warm_database(app: tauri::AppHandle, server: ServerConfig) {
+    let start = std::time::Instant::now();
+    let warmed = http_client(&app)
+        .get(server.url("/api/workflows?limit=1"))
+        .send()
+        .await
+        .is_ok_and(|resp| resp.status().is_success());
+    let ms = start.elapsed().as_millis() as u64;
+    desktop_log!("Database warm-up {} in {ms}ms", if warmed { "done" } else { "failed" });
+    if let Ok(mut timings) = app.state::<StartupMetrics>().timings.lock() {
+        timings.warm_ms = Some(ms);
+        timings.warmed = warmed;
+    }
+}
+
+/// Poll for workflows that finished since the last check and notify about
+/// each one, but only while no app window has focus.
+async fn check_finished_workflows(app: &tauri::AppHandle, watch: &mut notify::WorkflowWatch) {
+    let enabled = app
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .is_ok_and(|s| s.notifications_enabled);
+    // An idle-stopped sidecar stays stopped; polling must not count as activity
+    let running = app.state::<SidecarState>().is_running();
+    if !enabled || !running {
+        watch.reset();
+        return;
+    }
+
+    let url = app.state::<SidecarState>().server().url("/api/workflows");
+    let body = match http_client(app).get(url).send().await {
+        Ok(resp) if resp.status().is_success() => resp.json::<serde_json::Value>().await.ok(),
+        _ => None,
+    };
+    let Some(workflows) = body.as_ref().and_then(|b| b["data"].as_array()) else {
+        return;
+    };
+    let finished = watch.update(workflows);
+    let focused = app
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false));
+    if focused {
+        return;
+    }
+    for workflow in finished {
+        let title = match workflow.status.as_str() {
+            "failed" => "Workflow failed",
+            _ => "Workflow completed",
+        };
+        let shown = app
+            .notification()
+            .builder()
+            .title(title)
+            .body(format!("{} — {}", workflow.name, workflow.status))
+            .show();
+        if let Err(e) = shown {
+            desktop_log!("Warning: could not show notification: {e}");
+        }
+    }
+}
+
+/// Turn workflow completion notifications on or off.
+#[tauri::command]
+fn set_notifications_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.notifications_enabled = enabled;
+    settings::save(&settings)
+}
+
+/// Another launch while this instance runs: focus the main window. Any
+/// `caw://` link it was opened with reaches `open_deep_link` through the
+/// deep-link plugin. The second process exits without ever reaching setup,
+/// so the running sidecar is untouched.
+fn on_second_instance(app: &tauri::AppHandle, _argv: Vec<String>, _cwd: String) {
+    desktop_log!("Second launch detected; focusing the existing window");
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Focus the main window and navigate it to the route a `caw://` URL names.
+/// Links that don't parse are logged and dropped.
+fn open_deep_link(app: &tauri::AppHandle, url: &str) {
+    let route = match deeplink::route(url) {
+        Ok(route) => route,
+        Err(e) => {
+            desktop_log!("Warning: ignoring deep link: {e}");
+            return;
+        }
+    };
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+    if let Ok(mut target) = window.url() {
+        target.set_path(&route);
+        target.set_query(None);
+        target.set_fragment(None);
+        if let Err(e) = window.navigate(target) {
+            desktop_log!("Warning: could not open {url}: {e}");
+        }
+    }
+}
+
+/// What `take_over_stale_server` did with a sidecar a crashed session left.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Takeover {
+    /// There was none, or it was left alone.
+    None,
+    /// Still serving the database on the port, so it is used as is.
+    Adopted(u32),
+    /// Killed while it held the port, which may take a moment to free up.
+    Killed,
+}
+
+/// Deal with the sidecar a session that crashed before stopping it recorded
+/// in `caw.pid` for `db_path`. With `reuse`, one still on `port` is adopted;
+/// otherwise it is killed so a fresh sidecar can start. Servers the app didn't
+/// record, such as one started by hand, are left alone.
+fn take_over_stale_server(port: u16, db_path: &str, reuse: bool) -> Takeover {
+    let Some(pid) = process::read_pid_file(db_path) else {
+        return Takeover::None;
+    };
+    let Some(stale) = procs::caw_server(pid) else {
+        // It exited, or the PID was reused by something else
+        process::remove_pid_file(db_path);
+        return Takeover::None;
+    };
+    // The file sits next to the database, so it may belong to a neighbour
+    if stale.db.as_deref() != Some(db_path) {
+        return Takeover::None;
+    }
+    let on_port = stale.port.as_deref() == Some(port.to_string().as_str());
+    if reuse && on_port {
+        desktop_log!("Reusing caw server {pid} already running on port {port}");
+        return Takeover::Adopted(pid);
+    }
+    desktop_log!("Killing stale caw server {pid} left by a previous session");
+    if let Err(e) = procs::kill(pid) {
+        desktop_log!("Warning: could not kill stale server {pid}: {e}");
+        return Takeover::None;
+    }
+    if on_port {
+        Takeover::Killed
+    } else {
+        Takeover::None
+    }
+}
+
+/// Spawn the sidecar once a killed stale server has released `port`, or on
+/// the next free port for this run if it hasn't let go within two seconds.
+/// Runs after setup so the window isn't held up meanwhile. Returns whether a
+/// sidecar was spawned.
+async fn spawn_after_takeover(app: &tauri::AppHandle, port: u16) -> bool {
+    for _ in 0..20 {
+        if server::is_port_free(port) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let state = app.state::<SidecarState>();
+    if !server::is_port_free(port) {
+        match server::find_free_port(port) {
+            Some(free) => {
+                desktop_log!("Port {port} is in use; starting the sidecar on {free}");
+                state.server.lock().unwrap_or_else(PoisonError::into_inner).port = free;
+                if let Ok(mut source) = app.state::<ProjectState>().port_source.lock() {
+                    *source = project::Source::Session;
+                }
+            }
+            None => desktop_log!("Warning: no free port found from {port}"),
+        }
+    }
+    let Ok(db_path) = state.db_path() else {
+        return false;
+    };
+    warn_if_sidecar_modified(app);
+    // Hold the lock while spawning so the crash watcher sees the child
+    let mut guard = state.sidecar.lock().unwrap_or_else(PoisonError::into_inner);
+    if state.shutting_down.load(Ordering::SeqCst) {
+        return false;
+    }
+    let child = spawn_sidecar(app, &db_path).ok();
+    let spawned = child.is_some();
+    *guard = child.map(Sidecar::Child);
+    spawned
+}
+
+/// Memory and CPU use of the running sidecar, to spot one that is alive but
+/// pegged. `None` when no sidecar is running.
+#[tauri::command]
+async fn server_resource_usage(
+    app: tauri::AppHandle,
+) -> Result<Option<procs::ResourceUsage>, String> {
+    let Some(pid) = app.state::<SidecarState>().pid() else {
+        return Ok(None);
+    };
+    tauri::async_runtime::spawn_blocking(move || procs::usage(pid))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Every caw server process on the machine, including orphans from crashes.
+#[tauri::command]
+async fn list_caw_processes() -> Result<Vec<procs::CawProcess>, String> {
+    tauri::async_runtime::spawn_blocking(procs::list)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Kill a caw server by pid. If it is the managed sidecar, it is released
+/// first so the crash watcher doesn't restart it.
+#[tauri::command]
+async fn kill_caw_process(app: tauri::AppHandle, pid: u32) -> Result<(), String> {
+    {
+        let state = app.state::<SidecarState>();
+        let mut guard = state.sidecar.lock().map_err(|e| e.to_string())?;
+        if guard.as_ref().is_some_and(|sidecar| sidecar.pid() == pid) {
+            guard.take();
+        }
+    }
+    tauri::async_runtime::spawn_blocking(move || procs::kill(pid))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// HOME, PATH, git availability, and `CAW_*` variables as the backend sees them,
+/// for diagnosing "used the global db" or "used /tmp" surprises.
+#[tauri::command]
+async fn get_env_report() -> Result<environment::EnvReport, String> {
+    tauri::async_runtime::spawn_blocking(environment::report)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// How long each startup phase took on this launch.
+#[tauri::command]
+fn startup_metrics(app: tauri::AppHandle) -> StartupTimings {
+    app.state::<StartupMetrics>().snapshot()
+}
+
+/// Whether the app launched in safe mode, plus where to find the logs.
+#[tauri::command]
+fn safe_mode_status(app: tauri::AppHandle) -> serde_json::Value {
+    serde_json::json!({
+        "enabled": app.state::<SafeMode>().0,
+        "logs_dir": logs::logs_dir(),
+    })
+}
+
+/// Features the running sidecar supports, so the UI can hide ones it lacks.
+#[tauri::command]
+async fn server_capabilities(app: tauri::AppHandle) -> Result<Capabilities, String> {
+    sidecar_capabilities(&app).await
+}
+
+/// The effective sidecar configuration, where each value came from, and the
+/// project `.caw/config.toml` if one was loaded.
+#[tauri::command]
+fn get_config(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    effective_config(&app)
+}
+
+fn effective_config(app: &tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let state = app.state::<SidecarState>();
+    let server = state.server();
+    let db = state.db.lock().map_err(|e| e.to_string())?.clone();
+    let (pinned, last_used) = db_preferences(app)?;
+    let env_path = db::env_db_override().map(|(_, raw)| db::expand_home(&raw));
+    let db_source = match db.mode {
+        DbMode::Custom if env_path.as_deref() == Some(db.path.as_str()) => project::Source::Env,
+        _ if pinned.as_deref() == Some(db.path.as_str()) => project::Source::Settings,
+        DbMode::Custom if last_used.as_deref() == Some(db.path.as_str()) => {
+            project::Source::Settings
+        }
+        DbMode::Project => project::Source::Project,
+        DbMode::Global => project::Source::Default,
+        _ => project::Source::Session,
+    };
+    let extra_args_source = if server.extra_args.is_empty() {
+        project::Source::Default
+    } else {
+        project::Source::Project
+    };
+
+    let project = app.state::<ProjectState>();
+    let source = |source: &Mutex<project::Source>| {
+        source.lock().map(|s| *s).unwrap_or(project::Source::Default)
+    };
+    Ok(serde_json::json!({
+        "port": { "value": server.port, "source": source(&project.port_source) },
+        "transport": {
+            "value": server.transport,
+            "source": source(&project.transport_source),
+        },
+        "extra_args": { "value": server.extra_args, "source": extra_args_source },
+        "db": { "path": db.path, "mode": db.mode, "source": db_source },
+        "project": project.loaded,
+    }))
+}
+
+#[tauri::command]
+fn get_db_mode(app: tauri::AppHandle) -> Result<DbMode, String> {
+    let state = app.state::<SidecarState>();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(db.mode)
+}
+
+/// Switch between project-local, global, and custom database modes, then
+/// restart the sidecar against the newly selected database.
+#[tauri::command]
+async fn set_db_mode(
+    app: tauri::AppHandle,
+    mode: String,
+    path: Option<String>,
+) -> Result<serde_json::Value, String> {
+    let mode = DbMode::parse(&mode)?;
+    let path = match mode {
+        DbMode::Project => {
+            db::project_db_path().ok_or_else(|| "Not inside a git repository".to_string())?
+        }
+        DbMode::Global => db::global_db_path(),
+        DbMode::Custom => path
+            .filter(|p| !p.trim().is_empty())
+            .map(|p| db::expand_home(&p))
+            .ok_or_else(|| "Custom mode requires a database path".to_string())?,
+        DbMode::Temporary => {
+            let _drain = drain_requests(&app).await?;
+            return switch_to_memory_db(&app).await;
+        }
+    };
+    if mode == DbMode::Custom {
+        db::validate_db_path(&path)?;
+    }
+
+    let _drain = drain_requests(&app).await?;
+    let pinned = (mode == DbMode::Custom).then(|| path.clone());
+    set_active_db(&app, ResolvedDb { path, mode }, pinned)?;
+    restart_sidecar(&app).await
+}
+
+/// Pick a database file in a native dialog, restart the sidecar on it, and
+/// pin it as the default for future launches. `None` if the dialog was cancelled.
+#[tauri::command]
+async fn open_database(app: tauri::AppHandle) -> Result<Option<serde_json::Value>, String> {
+    let dialog = app.dialog().clone();
+    let picked = tauri::async_runtime::spawn_blocking(move || {
+        dialog
+            .file()
+            .set_title("Open Database")
+            .add_filter("SQLite database", &["db"])
+            .blocking_pick_file()
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked.into_path().map_err(|e| e.to_string())?;
+    switch_to_db(&app, path.to_string_lossy().into_owned()).await.map(Some)
+}
+
+/// Re-run database resolution for the repo around `path` (e.g. after moving to
+/// another repo) and, if it now picks a different database, restart the
+/// sidecar on it. The app's own working directory never changes, so the
+/// frontend says where to look. Bursts of calls are debounced so a flurry of
+/// focus or directory events restarts at most once.
+#[tauri::command]
+async fn reresolve_db(app: tauri::AppHandle, path: String) -> Result<serde_json::Value, String> {
+    let reresolve = app.state::<Reresolve>();
+    let mut last = reresolve.0.lock().await;
+    let current = app.state::<SidecarState>().db.lock().map_err(|e| e.to_string())?.clone();
+    if last.is_some_and(|at| at.elapsed() < RERESOLVE_DEBOUNCE) {
+        return Ok(serde_json::json!({ "changed": false, "db_path": current.path }));
+    }
+    *last = Some(std::time::Instant::now());
+    if current.mode == DbMode::Temporary {
+        return Err("The active database is temporary; save or discard it first".to_string());
+    }
+
+    let (pinned, last_used) = db_preferences(&app)?;
+    let (resolved, provenance) = tauri::async_runtime::spawn_blocking(move || {
+        db::resolve_db_path_in(
+            std::path::Path::new(&path),
+            pinned.as_deref(),
+            last_used.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if resolved.path == current.path {
+        return Ok(serde_json::json!({ "changed": false, "db_path": current.path }));
+    }
+
+    desktop_log!("Database re-resolved from {} to {}", current.path, resolved.path);
+    let _drain = drain_requests(&app).await?;
+    {
+        // The pin (if any) is unchanged, so only the managed state moves
+        let state = app.state::<SidecarState>();
+        *state.db.lock().map_err(|e| e.to_string())? = resolved.clone();
+    }
+    let mut result = restart_sidecar(&app).await?;
+    result["changed"] = true.into();
+    result["db_path"] = resolved.path.into();
+    result["previous"] = current.path.into();
+    result["source"] = serde_json::json!(provenance.source);
+    Ok(result)
+}
+
+/// Restart the sidecar on a throwaway database in the temp dir. Nothing is
+/// saved unless `persist_memory_db` is called; the file is deleted on exit.
+#[tauri::command]
+async fn use_memory_db(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let _drain = drain_requests(&app).await?;
+    switch_to_memory_db(&app).await
+}
+
+async fn switch_to_memory_db(app: &tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let path = db::temp_db_path();
+    let state = app.state::<SidecarState>();
+    let previous = std::mem::replace(
+        &mut *state.db.lock().map_err(|e| e.to_string())?,
+        ResolvedDb {
+            path: path.clone(),
+            mode: DbMode::Temporary,
+        },
+    );
+    if previous.mode == DbMode::Temporary {
+        db::remove_db_files(&previous.path);
+    }
+
+    let mut result = restart_sidecar(app).await?;
+    result["db_path"] = path.into();
+    result["ephemeral"] = true.into();
+    Ok(result)
+}
+
+/// Save the temporary database to `path` and switch to it as a custom db.
+#[tauri::command]
+async fn persist_memory_db(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<serde_json::Value, String> {
+    let state = app.state::<SidecarState>();
+    let current = state.db.lock().map_err(|e| e.to_string())?.clone();
+    if current.mode != DbMode::Temporary {
+        return Err("The active database is not temporary".to_string());
+    }
+    let dest = db::expand_home(&path);
+
+    // Stop first so the WAL is folded in and the copy is complete
+    let _drain = drain_requests(&app).await?;
+    if let Some(sidecar) = take_sidecar(&app)? {
+        process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await;
+    }
+    let src = current.path.clone();
+    let target = dest.clone();
+    let copied = tauri::async_runtime::spawn_blocking(move || db::copy_db(&src, &target))
+        .await
+        .map_err(|e| e.to_string())?;
+    if let Err(e) = copied {
+        let _ = restart_sidecar(&app).await;
+        return Err(e);
+    }
+
+    set_active_db(
+        &app,
+        ResolvedDb {
+            path: dest.clone(),
+            mode: DbMode::Custom,
+        },
+        Some(dest.clone()),
+    )?;
+    let mut result = restart_sidecar(&app).await?;
+    result["db_path"] = dest.into();
+    Ok(result)
+}
+
+/// The pinned and last-used database paths from desktop settings, the inputs
+/// `resolve_db_path` takes besides the environment and working directory.
+fn db_preferences(app: &tauri::AppHandle) -> Result<(Option<String>, Option<String>), String> {
+    let settings = app.state::<SettingsState>();
+    let settings = settings.0.lock().map_err(|e| e.to_string())?;
+    Ok((settings.db_path.clone(), settings.last_db_path.clone()))
+}
+
+/// Watcher on the sidecar's database file; replaced on each spawn.
+#[derive(Default)]
+struct DbWatch(Mutex<Option<::notify::RecommendedWatcher>>);
+
+/// Restart the sidecar when its database file is deleted, moved, or replaced
+/// out from under it (e.g. `git clean` or a restore), so it reopens the file
+/// now at that path instead of serving from a stale handle.
+fn watch_db(app: &tauri::AppHandle, db_path: &str) {
+    let Some(watch) = app.try_state::<DbWatch>() else {
+        return;
+    };
+    let handle = app.clone();
+    let path = db_path.to_string();
+    let watcher = dbwatch::watch(db_path, move || {
+        let state = handle.state::<SidecarState>();
+        // Our own switches and stops change these first; only react to outside changes
+        let current = state.spawned_db.lock().is_ok_and(|db| db.as_deref() == Some(&path));
+        if !current || !state.is_running() || handle.state::<Supervision>().is_paused() {
+            return;
+        }
+        desktop_log!("Database file {path} was moved or replaced; restarting the sidecar");
+        let handle = handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = restart_server(handle, None).await {
+                desktop_log!("Warning: restart after database change failed: {}", e.message);
+            }
+        });
+    });
+    let watcher = watcher
+        .map_err(|e| desktop_log!("Warning: not watching {db_path} for changes: {e}"))
+        .ok();
+    if let Ok(mut current) = watch.0.lock() {
+        *current = watcher;
+    }
+}
+
+/// Persist `db_path` as the last-used database so the next launch outside a
+/// git repo reopens it. Temporary databases are skipped; they don't outlive the app.
+fn remember_db(app: &tauri::AppHandle, db_path: &str) {
+    let temporary = app
+        .state::<SidecarState>()
+        .db
+        .lock()
+        .is_ok_and(|db| db.mode == DbMode::Temporary);
+    if temporary {
+        return;
+    }
+    let recent = {
+        let state = app.state::<SettingsState>();
+        let Ok(mut settings) = state.0.lock() else {
+            return;
+        };
+        if settings.last_db_path.as_deref() == Some(db_path)
+            && settings.recent_dbs.first().map(String::as_str) == Some(db_path)
+        {
+            return;
+        }
+        settings.last_db_path = Some(db_path.to_string());
+        settings.recent_dbs.retain(|path| path != db_path);
+        settings.recent_dbs.insert(0, db_path.to_string());
+        settings.recent_dbs.truncate(RECENT_DBS_LIMIT);
+        if let Err(e) = settings::save(&settings) {
+            desktop_log!("Warning: could not remember the last-used database: {e}");
+        }
+        settings.recent_dbs.clone()
+    };
+    if let Some(menu) = app.try_state::<RecentDbsMenu>() {
+        if let Err(e) = fill_recent_dbs_menu(app, &menu.0, &recent) {
+            desktop_log!("Warning: could not update Recent Databases: {e}");
+        }
+    }
+}
+
+/// Restart the sidecar on the database at `path` and pin it as the default,
+/// as when picked in `open_database` or from Recent Databases.
+async fn switch_to_db(app: &tauri::AppHandle, path: String) -> Result<serde_json::Value, String> {
+    db::validate_db_path(&path)?;
+    let _drain = drain_requests(app).await?;
+    let db = ResolvedDb {
+        path: path.clone(),
+        mode: DbMode::Custom,
+    };
+    set_active_db(app, db, Some(path.clone()))?;
+    let mut result = restart_sidecar(app).await?;
+    result["db_path"] = path.into();
+    Ok(result)
+}
+
+/// Pin the database opened at launch regardless of the working directory, or
+/// clear the pin with `None`. The running sidecar is left alone; use
+/// `set_db_mode` to switch now.
+#[tauri::command]
+fn set_default_db(app: tauri::AppHandle, path: Option<String>) -> Result<serde_json::Value, String> {
+    let path = path.filter(|p| !p.trim().is_empty()).map(|p| db::expand_home(&p));
+    if let Some(path) = &path {
+        db::validate_db_path(path)?;
+    }
+    let active = app.state::<SidecarState>().db_path()?;
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.db_path = path.clone();
+    settings::save(&settings)?;
+    Ok(serde_json::json!({
+        "db_path": path,
+        "restart_required": path.as_ref().is_some_and(|p| *p != active),
+    }))
+}
+
+/// Record the active database in managed state and persist (or clear) the pin.
+fn set_active_db(
+    app: &tauri::AppHandle,
+    db: ResolvedDb,
+    pinned: Option<String>,
+) -> Result<(), String> {
+    let previous = std::mem::replace(
+        &mut *app.state::<SidecarState>().db.lock().map_err(|e| e.to_string())?,
+        db,
+    );
+    if previous.mode == DbMode::Temporary {
+        db::remove_db_files(&previous.path);
+    }
+
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.db_path = pinned;
+    settings::save(&settings)
+}
+
+/// Clear stale `-wal`/`-shm` files left by a crash. Without `confirm` this only
+/// reports what would be removed; with it, the files are backed up first.
+#[tauri::command]
+async fn repair_lock(app: tauri::AppHandle, confirm: bool) -> Result<LockRepair, String> {
+    let db_path = app.state::<SidecarState>().db_path()?;
+    let repair = tauri::async_runtime::spawn_blocking(move || db::repair_lock(&db_path, confirm))
+        .await
+        .map_err(|e| e.to_string())??;
+    if let LockRepair::Repaired { backup, removed } = &repair {
+        desktop_log!("Removed stale {removed:?}; backup at {backup}");
+    }
+    Ok(repair)
+}
+
+/// Rename the active database (and its wal/shm files) within its directory,
+/// then restart the sidecar against the new file.
+#[tauri::command]
+async fn rename_database(
+    app: tauri::AppHandle,
+    new_name: String,
+) -> Result<serde_json::Value, String> {
+    let state = app.state::<SidecarState>();
+    let old_path = state.db_path()?;
+
+    // Hold the claim from stop to restart, so no other restart lands in between
+    let _restarting = state.restarting.claim().ok_or_else(|| restart_busy(&app).message)?;
+    let config = startup_health_config(&app);
+    let _drain = drain_requests(&app).await?;
+    if let Some(sidecar) = take_sidecar(&app)? {
+        process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await;
+    }
+
+    let renamed =
+        tauri::async_runtime::spawn_blocking(move || db::rename_db_files(&old_path, &new_name))
+            .await
+            .unwrap_or_else(|e| Err(e.to_string()));
+    let new_path = match renamed {
+        Ok(path) => path,
+        Err(e) => {
+            // Bring the server back on the untouched database before reporting
+            let _ = respawn_sidecar(&app, config).await;
+            return Err(e);
+        }
+    };
+
+    set_active_db(
+        &app,
+        ResolvedDb {
+            path: new_path.clone(),
+            mode: DbMode::Custom,
+        },
+        Some(new_path),
+    )?;
+    respawn_sidecar(&app, config).await.map_err(|failure| failure.message)
+}
+
+/// Merge the workflows in the `from_mode` database ("global" or "project") into
+/// the `to_mode` one, then restart the sidecar against the destination. IDs that
+/// collide with different rows are remapped; the source is left untouched.
+#[tauri::command]
+async fn migrate_db(
+    app: tauri::AppHandle,
+    from_mode: String,
+    to_mode: String,
+) -> Result<serde_json::Value, String> {
+    let mode_path = |mode: &str| -> Result<(DbMode, String), String> {
+        match DbMode::parse(mode)? {
+            DbMode::Project => db::project_db_path()
+                .map(|path| (DbMode::Project, path))
+                .ok_or_else(|| "Not inside a git repository".to_string()),
+            DbMode::Global => Ok((DbMode::Global, db::global_db_path())),
+            other => Err(format!(
+                "Only project and global databases can be migrated, not {other:?}"
+            )),
+        }
+    };
+    let (_, src) = mode_path(&from_mode)?;
+    let (to_mode, dest) = mode_path(&to_mode)?;
+    if src == dest {
+        return Err("Source and destination are the same database".to_string());
+    }
+    if !std::path::Path::new(&src).is_file() {
+        return Err(format!("Source database not found: {src}"));
+    }
+
+    // Stop first so the WAL is folded in and nothing writes mid-merge
+    let _drain = drain_requests(&app).await?;
+    if let Some(sidecar) = take_sidecar(&app)? {
+        process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await;
+    }
+    let target = dest.clone();
+    let merged = tauri::async_runtime::spawn_blocking(move || migrate::merge_into(&src, &target))
+        .await
+        .map_err(|e| e.to_string())?;
+    let report = match merged {
+        Ok(report) => report,
+        Err(e) => {
+            // Bring the server back on the untouched database before reporting
+            let _ = restart_sidecar(&app).await;
+            return Err(e);
+        }
+    };
+    desktop_log!("Migrated {} into {}", report.source, report.destination);
+
+    set_active_db(
+        &app,
+        ResolvedDb {
+            path: dest,
+            mode: to_mode,
+        },
+        None,
+    )?;
+    let mut result = restart_sidecar(&app).await?;
+    result["migration"] = serde_json::json!(report);
+    Ok(result)
+}
+
+/// Stop the sidecar once the app has been idle past `idle_stop_minutes`.
+async fn stop_if_idle(app: &tauri::AppHandle) {
+    let minutes = match app.state::<SettingsState>().0.lock() {
+        Ok(settings) => settings.idle_stop_minutes,
+        Err(_) => return,
+    };
+    let Some(threshold) = idle::threshold(minutes) else {
+        return;
+    };
+    let activity = app.state::<Activity>();
+    let focused = app
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false));
+    if focused || activity.idle_for() < threshold {
+        return;
+    }
+
+    let _wake = activity.wake.lock().await;
+    let Ok(Some(sidecar)) = take_sidecar(app) else {
+        return;
+    };
+    activity.set_idle_stopped(true);
+    process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await;
+    desktop_log!("Stopped sidecar after {minutes} idle minutes");
+    let _ = app.emit("sidecar://idle-stopped", serde_json::json!({ "minutes": minutes }));
+}
+
+/// Record activity and, if the sidecar was stopped for idling, restart it and
+/// wait for it to become healthy.
+async fn wake_sidecar(app: &tauri::AppHandle) -> Result<(), String> {
+    let activity = app.state::<Activity>();
+    activity.touch();
+    if !activity.is_idle_stopped() {
+        return Ok(());
+    }
+
+    let _wake = activity.wake.lock().await;
+    if !activity.is_idle_stopped() {
+        return Ok(());
+    }
+    if !app.state::<SidecarState>().is_running() {
+        restart_sidecar(app).await?;
+    }
+    activity.set_idle_stopped(false);
+    Ok(())
+}
+
+/// Capabilities of the running sidecar, fetched once per spawn.
+async fn sidecar_capabilities(app: &tauri::AppHandle) -> Result<Capabilities, String> {
+    let state = app.state::<SidecarState>();
+    let pid = state.pid().ok_or_else(|| "Server is not running".to_string())?;
+    if let Some((cached_pid, caps)) = &*state.capabilities.lock().map_err(|e| e.to_string())? {
+        if *cached_pid == pid {
+            return Ok(caps.clone());
+        }
+    }
+
+    let url = state.server().url("/api/capabilities");
+    let caps = capabilities::fetch(&http_client(app), &url).await?;
+    *state.capabilities.lock().map_err(|e| e.to_string())? = Some((pid, caps.clone()));
+    Ok(caps)
+}
+
+fn take_sidecar(app: &tauri::AppHandle) -> Result<Option<Sidecar>, String> {
+    let state = app.state::<SidecarState>();
+    let mut guard = state.sidecar.lock().map_err(|e| e.to_string())?;
+    app_nap::end();
+    Ok(guard.take())
+}
+
+fn stop_result(outcome: Option<StopOutcome>) -> serde_json::Value {
+    match outcome {
+        Some(outcome) => serde_json::json!({
+            "success": true,
+            "method": outcome.method,
+            "exited": outcome.exited,
+        }),
+        None => serde_json::json!({ "success": true, "method": "none", "exited": true }),
+    }
+}
+
+/// Whether the git repo around `path` (the directory the user is now working
+/// in) differs from the one at launch. The app's own working directory never
+/// changes, so the frontend says where to look. Emits `workspace://changed` so
+/// the frontend can offer a switch.
+#[tauri::command]
+async fn workspace_changed(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    let launch_root = app.state::<WorkspaceState>().git_root.clone();
+    let current_root =
+        tauri::async_runtime::spawn_blocking(move || db::git_root_in(std::path::Path::new(&path)))
+            .await
+            .map_err(|e| e.to_string())?;
+    if current_root == launch_root {
+        return Ok(false);
+    }
+
+    let _ = app.emit(
+        "workspace://changed",
+        serde_json::json!({ "previous": launch_root, "current": current_root }),
+    );
+    Ok(true)
+}
+
+/// Call one of the sidecar's `/api/admin` endpoints, failing cleanly when the
+/// sidecar is down or too old to have it.
+async fn admin_request(
+    app: &tauri::AppHandle,
+    method: reqwest::Method,
+    path: &str,
+    feature: &str,
+) -> Result<serde_json::Value, String> {
+    if !app.state::<SidecarState>().is_running() {
+        return Err("The server is not running".to_string());
+    }
+    if let Ok(caps) = sidecar_capabilities(app).await {
+        caps.require(feature)?;
+    }
+    let server = app.state::<SidecarState>().server();
+    let resp = http_client(app)
+        .request(method, server.url(path))
+        .timeout(std::time::Duration::from_secs(60))
+        .send()
+        .await
+        .map_err(|e| format!("Server is not reachable: {e}"))?;
+
+    let status = resp.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("{feature} is not supported by this server version"));
+    }
+    let mut body: serde_json::Value = resp.json().await.unwrap_or(serde_json::Value::Null);
+    if !status.is_success() {
+        return Err(format!("Server returned {status}: {body}"));
+    }
+    Ok(body["data"].take())
+}
+
+/// Apply any pending schema migrations without restarting the app.
+#[tauri::command]
+async fn run_migrations(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let _drain = drain_requests(&app).await?;
+    let body = admin_request(
+        &app,
+        reqwest::Method::POST,
+        "/api/admin/migrate",
+        capabilities::MIGRATE,
+    )
+    .await?;
+    desktop_log!("Ran database migrations: {body}");
+    Ok(serde_json::json!({ "success": true, "result": body }))
+}
+
+/// Run `PRAGMA integrity_check` through the sidecar. `healthy` is true when
+/// SQLite reports a bare "ok"; otherwise `errors` lists what it found.
+#[tauri::command]
+async fn check_db_integrity(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let body = admin_request(
+        &app,
+        reqwest::Method::POST,
+        "/api/admin/integrity-check",
+        capabilities::INTEGRITY_CHECK,
+    )
+    .await?;
+    let messages: Vec<String> = match &body["result"] {
+        serde_json::Value::String(line) => vec![line.clone()],
+        serde_json::Value::Array(lines) => {
+            lines.iter().filter_map(|l| l.as_str().map(str::to_string)).collect()
+        }
+        _ => Vec::new(),
+    };
+    let healthy = messages == ["ok"];
+    let errors = if healthy { Vec::new() } else { messages };
+    Ok(serde_json::json!({ "healthy": healthy, "errors": errors, "raw": body }))
+}
+
+/// Set the app theme ("light", "dark", or "system"), update native window
+/// chrome to match, and persist the choice.
+#[tauri::command]
+fn set_theme(app: tauri::AppHandle, theme: String) -> Result<(), String> {
+    let theme = Theme::parse(&theme)?;
+    for window in app.webview_windows().values() {
+        appearance::apply_theme(window, theme);
+    }
+
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.theme = theme;
+    settings::save(&settings)
+}
+
+/// Re-read appearance settings from `desktop.json` and apply them to every
+/// open window without a relaunch. Each setting is applied on its own so one
+/// failure doesn't block the rest; failures are logged and returned.
+#[tauri::command]
+fn apply_appearance(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let saved = settings::load();
+    let state = app.state::<SettingsState>();
+    let settings = {
+        let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+        settings.theme = saved.theme;
+        settings.always_on_top = saved.always_on_top;
+        settings.clone()
+    };
+
+    let windows = app.webview_windows();
+    let mut failed = Vec::new();
+    for (label, window) in &windows {
+        for (setting, error) in appearance::apply_appearance(window, &settings) {
+            desktop_log!("Warning: failed to apply {setting} to window {label}: {error}");
+            failed.push(serde_json::json!({
+                "window": label,
+                "setting": setting,
+                "error": error,
+            }));
+        }
+    }
+    Ok(serde_json::json!({ "windows": windows.len(), "failed": failed }))
+}
+
+/// Back up and remove `desktop.json`, then load defaults. Settings read live
+/// take effect now; the rest are reported as needing a restart. The workflows
+/// database is never touched.
+#[tauri::command]
+fn reset_settings(app: tauri::AppHandle, confirm: bool) -> Result<serde_json::Value, String> {
+    if !confirm {
+        return Err("Resetting settings requires confirm: true".to_string());
+    }
+    let backup = settings::back_up_and_remove()?;
+    let defaults = DesktopSettings::default();
+
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    let old = std::mem::replace(&mut *settings, defaults.clone());
+    drop(settings);
+
+    // Read where they're used, so the new value already applies.
+    let live = [
+        ("auto_restart_on_crash", old.auto_restart_on_crash != defaults.auto_restart_on_crash),
+        ("idle_stop_minutes", old.idle_stop_minutes != defaults.idle_stop_minutes),
+        (
+            "hang_watchdog",
+            old.hang_timeouts != defaults.hang_timeouts
+                || old.hang_window_secs != defaults.hang_window_secs,
+        ),
+        (
+            "request_drain_timeout_secs",
+            old.request_drain_timeout_secs != defaults.request_drain_timeout_secs,
+        ),
+        ("startup_timeout_secs", old.startup_timeout_secs != defaults.startup_timeout_secs),
+        ("status_timeout_ms", old.status_timeout_ms != defaults.status_timeout_ms),
+        ("reuse_existing_server", old.reuse_existing_server != defaults.reuse_existing_server),
+        ("prewarm_db", old.prewarm_db != defaults.prewarm_db),
+        ("notifications_enabled", old.notifications_enabled != defaults.notifications_enabled),
+        ("last_db_path", old.last_db_path != defaults.last_db_path),
+    ];
+    let mut applied: Vec<&str> =
+        live.into_iter().filter(|(_, changed)| *changed).map(|(name, _)| name).collect();
+    let mut restart_required = Vec::new();
+    if old.unread_only != defaults.unread_only {
+        if let Some(item) = app.try_state::<UnreadMenuItem>() {
+            let _ = item.0.set_checked(defaults.unread_only);
+        }
+        let _ = app.emit(
+            "messages://unread-filter",
+            serde_json::json!({ "unread_only": defaults.unread_only }),
+        );
+        applied.push("unread_only");
+    }
+    if old.recent_dbs != defaults.recent_dbs {
+        if let Some(menu) = app.try_state::<RecentDbsMenu>() {
+            if let Err(e) = fill_recent_dbs_menu(&app, &menu.0, &defaults.recent_dbs) {
+                desktop_log!("Warning: could not update Recent Databases: {e}");
+            }
+        }
+        applied.push("recent_dbs");
+    }
+    if old.theme != defaults.theme {
+        for window in app.webview_windows().values() {
+            appearance::apply_theme(window, defaults.theme);
+        }
+        applied.push("theme");
+    }
+    if old.use_system_proxy != defaults.use_system_proxy {
+        let client = build_http_client(defaults.use_system_proxy).map_err(|e| e.to_string())?;
+        *app.state::<HttpClient>().0.lock().map_err(|e| e.to_string())? = client;
+        applied.push("use_system_proxy");
+    }
+    if old.activation_policy != defaults.activation_policy {
+        appearance::apply_activation_policy(&app, defaults.activation_policy);
+        applied.push("activation_policy");
+    }
+    if old.prevent_app_nap != defaults.prevent_app_nap {
+        let running = app.state::<SidecarState>().is_running();
+        if defaults.prevent_app_nap && running {
+            app_nap::begin();
+        }
+        applied.push("prevent_app_nap");
+    }
+    if old.always_on_top != defaults.always_on_top {
+        for window in app.webview_windows().values() {
+            let _ = window.set_always_on_top(defaults.always_on_top);
+        }
+        applied.push("always_on_top");
+    }
+    if old.chrome_mode != defaults.chrome_mode || old.traffic_lights != defaults.traffic_lights {
+        for window in app.webview_windows().values() {
+            apply_chrome_mode(window, defaults.chrome_mode, defaults.traffic_lights);
+        }
+        if let Some(item) = app.try_state::<ChromeMenuItem>() {
+            let _ = item.0.set_checked(defaults.chrome_mode == ChromeMode::Minimal);
+        }
+        if old.chrome_mode != defaults.chrome_mode {
+            applied.push("chrome_mode");
+        }
+        if old.traffic_lights != defaults.traffic_lights {
+            applied.push("traffic_lights");
+        }
+    }
+    if old.port != defaults.port {
+        restart_required.push("port");
+    }
+    if old.db_path != defaults.db_path {
+        restart_required.push("db_path");
+    }
+
+    Ok(serde_json::json!({
+        "backup": backup,
+        "applied": applied,
+        "restart_required": restart_required,
+    }))
+}
+
+/// Set whether the app has a Dock icon (`"regular"`) or not (`"accessory"`),
+/// and persist it. Only macOS has a Dock; elsewhere this just saves the choice.
+#[tauri::command]
+fn set_activation_policy(app: tauri::AppHandle, policy: String) -> Result<(), String> {
+    let policy = ActivationPolicy::parse(&policy)?;
+    appearance::apply_activation_policy(&app, policy);
+
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.activation_policy = policy;
+    settings::save(&settings)
+}
+
+/// Follow OS appearance changes when the user picked the "system" theme.
+fn on_theme_changed(window: &tauri::Window, theme: tauri::Theme) {
+    let app = window.app_handle();
+    let follows_system = app
+        .try_state::<SettingsState>()
+        .and_then(|state| state.0.lock().ok().map(|s| s.theme == Theme::System))
+        .unwrap_or(false);
+    let safe_mode = app.try_state::<SafeMode>().is_some_and(|mode| mode.0);
+    if !follows_system || safe_mode {
+        return;
+    }
+    if let Some(webview) = app.get_webview_window(window.label()) {
+        appearance::follow_system_theme(&webview, theme);
+    }
+}
+
+/// AppKit lays the traffic lights out again on every resize, including the
+/// one when a window leaves fullscreen, so put them back where we want them.
+fn on_resized(window: &tauri::Window) {
+    #[cfg(target_os = "macos")]
+    {
+        let app = window.app_handle();
+        if app.try_state::<SafeMode>().is_some_and(|mode| mode.0) {
+            return;
+        }
+        let Some((mode, lights)) = app
+            .try_state::<SettingsState>()
+            .and_then(|state| state.0.lock().ok().map(|s| (s.chrome_mode, s.traffic_lights)))
+        else {
+            return;
+        };
+        if let Some(webview) = app.get_webview_window(window.label()) {
+            // Fullscreen windows hide the traffic lights until hovered
+            if !webview.is_fullscreen().unwrap_or(false) {
+                apply_chrome_mode(&webview, mode, lights);
+            }
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = window;
+}
+
+/// Tell the frontend about focus changes so it can pause polling while in the
+/// background, and count focus as activity for the idle timer.
+fn on_focus_changed(window: &tauri::Window, focused: bool) {
+    let app = window.app_handle().clone();
+    let _ = app.emit(
+        "window://focus",
+        serde_json::json!({ "label": window.label(), "focused": focused }),
+    );
+    if !focused {
+        if let Some(activity) = app.try_state::<Activity>() {
+            activity.touch();
+        }
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = wake_sidecar(&app).await {
+            desktop_log!("Failed to wake sidecar: {e}");
+        }
+    });
+}
+
+/// Apply a list of workflow operations in one call, e.g. bulk retry or abandon.
+#[tauri::command]
+async fn batch_operation(
+    app: tauri::AppHandle,
+    ops: Vec<BatchOp>,
+) -> Result<Vec<BatchResult>, String> {
+    let _request = app.state::<RequestGate>().inner().enter().await;
+    let base_url = app.state::<SidecarState>().server().base_url();
+    Ok(batch::run_batch(http_client(&app), &base_url, ops).await)
+}
+
+/// Measure sidecar throughput: `requests` GETs of `path` (default `/health`)
+/// with `concurrency` in flight, over the shared client.
+#[tauri::command]
+async fn benchmark_server(
+    app: tauri::AppHandle,
+    requests: usize,
+    concurrency: usize,
+    path: Option<String>,
+) -> Result<bench::BenchReport, String> {
+    let _request = app.state::<RequestGate>().inner().enter().await;
+    wake_sidecar(&app).await?;
+    let base_url = app.state::<SidecarState>().server().base_url();
+    let url = api::sidecar_url(&base_url, path.as_deref().unwrap_or("/health"))?;
+    bench::run(http_client(&app), url, requests, concurrency).await
+}
+
+/// Database path the running sidecar actually opened. `/health` is plain text
+/// on current servers, so fall back to the diagnostics in `/api/config`.
+async fn server_db_path(app: &tauri::AppHandle) -> Result<String, String> {
+    let client = http_client(app);
+    let server = app.state::<SidecarState>().server();
+    let health = status_request(app).send().await;
+    let health: Option<serde_json::Value> = match health {
+        Ok(resp) => resp.json().await.ok(),
+        Err(e) => return Err(format!("Server is not reachable: {e}")),
+    };
+    if let Some(path) = health.as_ref().and_then(|h| h["db_path"].as_str()) {
+        return Ok(path.to_string());
+    }
+
+    let resp = client
+        .get(server.url("/api/config"))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("Server returned {}", resp.status()));
+    }
+    let config: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    config["data"]["diagnostics"]["dbPath"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Server did not report its database path".to_string())
+}
+
+/// The database the running sidecar was spawned with.
+#[tauri::command]
+fn current_db_path(app: tauri::AppHandle) -> Result<String, String> {
+    app.state::<SidecarState>()
+        .spawned_db
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or_else(|| "The server has not been started".to_string())
+}
+
+/// Check that the sidecar is running against the database the app thinks is
+/// active, e.g. after a database switch that only half completed.
+#[tauri::command]
+async fn verify_db_consistency(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let managed_db = app.state::<SidecarState>().db_path()?;
+    let server_db = server_db_path(&app).await?;
+    let canonical = |path: &str| std::fs::canonicalize(path).unwrap_or_else(|_| path.into());
+    let consistent = canonical(&server_db) == canonical(&managed_db);
+
+    let report = serde_json::json!({
+        "consistent": consistent,
+        "server_db": server_db,
+        "managed_db": managed_db,
+    });
+    if !consistent {
+        desktop_log!("Warning: sidecar is using {server_db} but the app expects {managed_db}");
+        let _ = app.emit("db://mismatch", &report);
+    }
+    Ok(report)
+}
+
+/// Re-run database resolution and explain which rule picked the path and
+/// from what inputs, alongside the database actually in use (which differs
+/// after a runtime switch or a read-only fallback).
+#[tauri::command]
+async fn db_path_provenance(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let (pinned, last_used) = db_preferences(&app)?;
+    let (_, provenance) = tauri::async_runtime::spawn_blocking(move || {
+        resolve_db_path(pinned.as_deref(), last_used.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let active = app.state::<SidecarState>().db.lock().map_err(|e| e.to_string())?.clone();
+
+    let mut report = serde_json::json!(provenance);
+    report["active"] = serde_json::json!({
+        "path": active.path,
+        "mode": active.mode,
+        "matches": active.path == provenance.path,
+    });
+    Ok(report)
+}
+
+/// Check up front that the active database really accepts writes, so the UI
+/// can warn before the user composes work that would fail to save.
+#[tauri::command]
+async fn can_write_db(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let db_path = app.state::<SidecarState>().db_path()?;
+    let result = tauri::async_runtime::spawn_blocking(move || db::check_db_writable(&db_path))
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(match result {
+        Ok(()) => serde_json::json!({ "writable": true, "reason": null }),
+        Err(reason) => serde_json::json!({ "writable": false, "reason": reason }),
+    })
+}
+
+/// Recent `/health` results (timestamp, ok, latency) for an uptime sparkline.
+#[tauri::command]
+fn health_history(app: tauri::AppHandle) -> Vec<health::HealthSample> {
+    app.state::<HealthHistory>().samples()
+}
+
+/// Recent database size samples plus the current db and `-wal` sizes.
+#[tauri::command]
+fn db_size_history(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let db_path = app.state::<SidecarState>().db_path()?;
+    Ok(serde_json::json!({
+        "samples": app.state::<SizeHistory>().samples(),
+        "current": stats::sample(&db_path),
+    }))
+}
+
+/// Free and total space on the filesystem holding the active database, plus
+/// its current size, so the UI can warn before the disk fills up.
+#[tauri::command]
+async fn db_disk_space(app: tauri::AppHandle) -> Result<stats::DiskSpace, String> {
+    let db_path = app.state::<SidecarState>().db_path()?;
+    tauri::async_runtime::spawn_blocking(move || stats::disk_space(&db_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Stop the managed sidecar and open a terminal with the same `caw --server`
+/// command line, run immediately when `execute` is set, otherwise just shown.
+/// The command is also copied to the clipboard.
+#[tauri::command]
+async fn open_sidecar_in_terminal(
+    app: tauri::AppHandle,
+    execute: bool,
+) -> Result<serde_json::Value, String> {
+    let state = app.state::<SidecarState>();
+    let sidecar = integrity::sidecar_path()?;
+    let args = state.server().sidecar_args(&state.db_path()?);
+    let command_line = terminal::sidecar_command_line(&sidecar.to_string_lossy(), &args);
+
+    // Free the port before handing off
+    if let Some(sidecar) = take_sidecar(&app)? {
+        process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await;
+    }
+    terminal::open(&app, &command_line, execute)?;
+    let _ = app.clipboard().write_text(command_line.clone());
+    Ok(serde_json::json!({ "command": command_line, "executed": execute }))
+}
+
+/// The exact argv the app uses to spawn the sidecar, as a vector and as a
+/// shell-quoted string (with credentials redacted) to copy and run by hand.
+#[tauri::command]
+fn sidecar_command_line(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let state = app.state::<SidecarState>();
+    let sidecar = integrity::sidecar_path()?.to_string_lossy().into_owned();
+    let args = state.server().sidecar_args(&state.db_path()?);
+    let command_line = terminal::sidecar_command_line(&sidecar, &args);
+    let argv: Vec<String> = std::iter::once(sidecar).chain(args).collect();
+    Ok(serde_json::json!({ "argv": argv, "command": command_line }))
+}
+
+/// Compare the bundled sidecar's SHA-256 against the hash embedded at build time.
+#[tauri::command]
+async fn verify_sidecar_integrity() -> Result<integrity::IntegrityReport, String> {
+    tauri::async_runtime::spawn_blocking(integrity::verify)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// The sidecar's most recent stdout and stderr lines, oldest first, kept in
+/// memory for a diagnostics view.
+#[tauri::command]
+fn server_logs(app: tauri::AppHandle) -> Vec<String> {
+    app.state::<SidecarState>().output_tail.lines()
+}
+
+/// Tail the sidecar log, keeping lines at or above `min_level` and/or
+/// containing `contains`. Lines without a recognisable level come back in `raw`.
+#[tauri::command]
+async fn server_logs_filtered(
+    lines: usize,
+    min_level: Option<String>,
+    contains: Option<String>,
+) -> Result<logs::FilteredLogs, String> {
+    let min_level = min_level.as_deref().map(logs::Level::parse).transpose()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        logs::tail_filtered(logs::SIDECAR_LOG, lines, min_level, contains.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Open a sidecar URL (default `/health`) in the system browser, e.g. to poke
+/// at endpoints by hand.
+#[tauri::command]
+fn open_api_in_browser(app: tauri::AppHandle, path: Option<String>) -> Result<String, String> {
+    let base_url = app.state::<SidecarState>().server().base_url();
+    let url = api::sidecar_url(&base_url, path.as_deref().unwrap_or("/health"))?;
+    app.opener()
+        .open_url(url.as_str(), None::<&str>)
+        .map_err(|e| format!("Failed to open browser: {e}"))?;
+    Ok(url.into())
+}
+
+/// Forward the sidecar's `/api/events` stream as `server://event` until cancelled,
+/// reconnecting with backoff whenever it drops while a sidecar is running.
+async fn run_event_stream(app: tauri::AppHandle, mut resp: Option<reqwest::Response>) {
+    let mut backoff = events::INITIAL_BACKOFF;
+    loop {
+        if let Some(connected) = resp.take() {
+            backoff = events::INITIAL_BACKOFF;
+            let _ = app.emit("server://event-stream", serde_json::json!({ "connected": true }));
+            let result = events::pump(connected, |event| {
+                let _ = app.emit("server://event", event);
+            })
+            .await;
+            if let Err(e) = result {
+                desktop_log!("Event stream dropped: {e}");
+            }
+            let _ = app.emit("server://event-stream", serde_json::json!({ "connected": false }));
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(events::MAX_BACKOFF);
+        // A deliberately stopped server isn't an outage; wait for it to return
+        if !app.state::<SidecarState>().is_running() {
+            continue;
+        }
+        let url = app.state::<SidecarState>().server().url("/api/events");
+        resp = events::connect(&http_client(&app), &url).await.ok();
+    }
+}
+
+/// Start forwarding live updates from the sidecar's SSE `/api/events` endpoint as
+/// `server://event`. Returns false if a subscription was already running.
+#[tauri::command]
+async fn subscribe_events(app: tauri::AppHandle) -> Result<bool, String> {
+    if let Some(task) = app.state::<EventStream>().0.lock().map_err(|e| e.to_string())?.as_ref() {
+        if !task.inner().is_finished() {
+            return Ok(false);
+        }
+    }
+    wake_sidecar(&app).await?;
+    let url = app.state::<SidecarState>().server().url("/api/events");
+    let resp = events::connect(&http_client(&app), &url).await?;
+
+    let task = tauri::async_runtime::spawn(run_event_stream(app.clone(), Some(resp)));
+    let state = app.state::<EventStream>();
+    let mut current = state.0.lock().map_err(|e| e.to_string())?;
+    if let Some(previous) = current.replace(task) {
+        previous.abort();
+    }
+    Ok(true)
+}
+
+/// Stop the `subscribe_events` stream. Returns false if none was running.
+#[tauri::command]
+fn unsubscribe_events(app: tauri::AppHandle) -> Result<bool, String> {
+    let task = app.state::<EventStream>().0.lock().map_err(|e| e.to_string())?.take();
+    match task {
+        Some(task) => {
+            task.abort();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Proxy a request to the sidecar so the frontend can reach it without CORS.
+/// `path` must be server-relative (e.g. `/api/workflows`); `timeout_ms`
+/// overrides the default timeout for slow operations (clamped to 100 ms–5 min).
+#[tauri::command]
+async fn api_request(
+    app: tauri::AppHandle,
+    method: String,
+    path: String,
+    body: Option<serde_json::Value>,
+    timeout_ms: Option<u64>,
+) -> Result<ApiResponse, String> {
+    let _request = app.state::<RequestGate>().inner().enter().await;
+    wake_sidecar(&app).await?;
+    let base_url = app.state::<SidecarState>().server().base_url();
+    api::proxy(&http_client(&app), &base_url, &method, &path, body, timeout_ms).await
+}
+
+/// How long the sidecar may take to become healthy on launch and restart.
+/// Clamped to 5–120 seconds; returns the value saved.
+#[tauri::command]
+fn set_startup_timeout(app: tauri::AppHandle, secs: u64) -> Result<u64, String> {
+    let secs = secs.clamp(health::STARTUP_TIMEOUT_MIN_SECS, health::STARTUP_TIMEOUT_MAX_SECS);
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.startup_timeout_secs = secs;
+    settings::save(&settings)?;
+    Ok(secs)
+}
+
+/// How long a restart waits for in-flight proxied requests before going ahead.
+#[tauri::command]
+fn set_request_drain_timeout(app: tauri::AppHandle, secs: u64) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.request_drain_timeout_secs = secs;
+    settings::save(&settings)
+}
+
+/// Configure the hung-sidecar watchdog: restart after `timeouts` consecutive
+/// `/health` timeouts within `window_secs`. `timeouts` of 0 disables it.
+#[tauri::command]
+fn set_hang_watchdog(app: tauri::AppHandle, timeouts: u32, window_secs: u64) -> Result<(), String> {
+    if timeouts > 0 && window_secs == 0 {
+        return Err("window_secs must be greater than 0".to_string());
+    }
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.hang_timeouts = timeouts;
+    settings.hang_window_secs = window_secs;
+    settings::save(&settings)
+}
+
+/// Choose whether to keep macOS App Nap off while a sidecar runs.
+#[tauri::command]
+fn set_prevent_app_nap(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    if enabled && app.state::<SidecarState>().is_running() {
+        app_nap::begin();
+    } else {
+        app_nap::end();
+    }
+
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.prevent_app_nap = enabled;
+    settings::save(&settings)
+}
+
+/// Route sidecar requests through the system proxy (`HTTP_PROXY` etc.) or,
+/// by default, connect to localhost directly.
+#[tauri::command]
+fn set_use_system_proxy(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let client = build_http_client(enabled).map_err(|e| e.to_string())?;
+    *app.state::<HttpClient>().0.lock().map_err(|e| e.to_string())? = client;
+
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.use_system_proxy = enabled;
+    settings::save(&settings)
+}
+
+#[tauri::command]
+fn get_auto_restart(app: tauri::AppHandle) -> Result<bool, String> {
+    let state = app.state::<SettingsState>();
+    let settings = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(settings.auto_restart_on_crash)
+}
+
+/// Turn crash auto-restart on or off. When off, a crash only emits
+/// `sidecar://crashed` and the process stays down for inspection.
 #[tauri::command]
-async fn server_status() -> Result<serde_json::Value, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(2))
-        .build()
-        .map_err(|e| e.to_string())?;
+fn set_auto_restart(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.auto_restart_on_crash = enabled;
+    settings::save(&settings)
+}
 
-    match client.get("http://localhost:3100/health").send().await {
-        Ok(resp) if resp.status().is_success() => {
-            Ok(serde_json::json!({ "running": true }))
-        }
-        _ => Ok(serde_json::json!({ "running": false })),
-    }
+/// Choose whether launch reuses a caw server left on the port by a crashed
+/// session (`true`) or kills it and spawns a fresh one (`false`).
+#[tauri::command]
+fn set_reuse_existing_server(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    settings.reuse_existing_server = enabled;
+    settings::save(&settings)
+}
+
+fn diagnostics(app: &tauri::AppHandle) -> serde_json::Value {
+    let state = app.state::<SidecarState>();
+    let db = state.db.lock().ok().map(|db| db.clone());
+    let pid = state.pid();
+    serde_json::json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "server_url": state.server().base_url(),
+        "db_path": db.as_ref().map(|db| db.path.clone()),
+        "db_mode": db.map(|db| db.mode),
+        "sidecar_pid": pid,
+        "recent_stderr": state.stderr_tail.lines(),
+        "last_crash": state.last_crash.lock().ok().and_then(|crash| crash.clone()),
+    })
+}
+
+/// Snapshot of app, platform, and sidecar state for bug reports.
+#[tauri::command]
+fn collect_diagnostics(app: tauri::AppHandle) -> serde_json::Value {
+    diagnostics(&app)
 }
 
+/// Sidecar output lines included in `copy_diagnostics`.
+const DIAGNOSTICS_LOG_LINES: usize = 50;
+
+/// Put a paste-ready Markdown bug report on the clipboard: app and sidecar
+/// versions, database, port, platform, and recent sidecar output, with home
+/// paths and credentials redacted. Returns the report.
 #[tauri::command]
-async fn restart_server(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
-    // Kill existing sidecar
+async fn copy_diagnostics(app: tauri::AppHandle) -> Result<String, String> {
+    let sidecar_version = match sidecar_capabilities(&app).await {
+        Ok(caps) => caps.version.unwrap_or_else(|| "not reported".to_string()),
+        Err(_) => "not reachable".to_string(),
+    };
     let state = app.state::<SidecarState>();
-    {
-        let mut guard = state.0.lock().map_err(|e| e.to_string())?;
-        if let Some(child) = guard.take() {
-            let _ = child.kill();
-        }
-    }
+    let db = state.db.lock().map_err(|e| e.to_string())?.clone();
+    let server = state.server();
+    let lines = state.output_tail.lines();
+    let recent = &lines[lines.len().saturating_sub(DIAGNOSTICS_LOG_LINES)..];
 
-    // Small delay to let the port free up
-    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let report = format!(
+        "## caw diagnostics\n\n\
+         - App version: {}\n\
+         - Sidecar version: {sidecar_version}\n\
+         - Database: {} ({:?})\n\
+         - Port: {}\n\
+         - OS: {} ({})\n\n\
+         ### Recent sidecar output\n\n```\n{}\n```\n",
+        env!("CARGO_PKG_VERSION"),
+        db.path,
+        db.mode,
+        server.port,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        recent.join("\n"),
+    );
+    let report = logs::redact(&report);
+    app.clipboard()
+        .write_text(report.clone())
+        .map_err(|e| format!("Failed to write clipboard: {e}"))?;
+    Ok(report)
+}
 
-    // Re-spawn sidecar
-    let db_path = resolve_db_path();
-    let sidecar = app.shell().sidecar("caw").map_err(|e| e.to_string())?;
-    let (_rx, child) = sidecar
-        .args(["--server", "--transport", "http", "--port", "3100", "--db", &db_path])
-        .spawn()
-        .map_err(|e| format!("Failed to spawn sidecar: {e}"))?;
+/// Zip desktop/sidecar logs and diagnostics (with home paths redacted) for support tickets.
+#[tauri::command]
+async fn export_logs(
+    app: tauri::AppHandle,
+    dest_path: String,
+) -> Result<serde_json::Value, String> {
+    let diagnostics =
+        serde_json::to_string_pretty(&diagnostics(&app)).map_err(|e| e.to_string())?;
+    let dest = dest_path.clone();
+    let size = tauri::async_runtime::spawn_blocking(move || {
+        let entries = [logs::Entry::Text("diagnostics.json", diagnostics)];
+        logs::export_zip(std::path::Path::new(&dest), &entries)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(serde_json::json!({ "path": dest_path, "size": size }))
+}
 
-    {
-        let mut guard = state.0.lock().map_err(|e| e.to_string())?;
-        *guard = Some(child);
+/// Zip a consistent copy of the active database with the effective config,
+/// logs, and diagnostics to `dest`, so a bug can be reproduced on a fresh caw.
+/// The snapshot holds the user's workflow data, so nothing is written until
+/// `confirm` is set; home paths and credentials are redacted from the text files.
+#[tauri::command]
+async fn capture_snapshot(
+    app: tauri::AppHandle,
+    dest: String,
+    confirm: bool,
+) -> Result<serde_json::Value, String> {
+    if !confirm {
+        return Ok(serde_json::json!({
+            "written": false,
+            "warning": "The snapshot contains a full copy of your workflows database. \
+                        Only share it with people you trust.",
+        }));
     }
+    let state = app.state::<SidecarState>();
+    let db_path = state.db_path()?;
 
-    // Poll health until ready
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(2))
-        .build()
-        .map_err(|e| e.to_string())?;
+    // Fold the WAL in first; VACUUM INTO below is consistent either way
+    let checkpoint = match sidecar_capabilities(&app).await {
+        Ok(caps) if caps.supports(capabilities::CHECKPOINT) == Some(false) => None,
+        Ok(_) => Some(
+            http_client(&app)
+                .post(state.server().url("/api/admin/checkpoint"))
+                .send()
+                .await
+                .is_ok_and(|resp| resp.status().is_success()),
+        ),
+        Err(_) => None,
+    };
 
-    for _ in 0..30 {
-        if let Ok(resp) = client.get("http://localhost:3100/health").send().await {
-            if resp.status().is_success() {
-                return Ok(serde_json::json!({ "success": true }));
-            }
-        }
-        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let mut config = effective_config(&app)?;
+    let extra_args = terminal::redact_args(&state.server().extra_args);
+    config["extra_args"]["value"] = serde_json::json!(extra_args);
+    if config["project"]["config"].is_object() {
+        config["project"]["config"]["extra_args"] = serde_json::json!(extra_args);
     }
+    let mut diagnostics = diagnostics(&app);
+    diagnostics["checkpointed"] = checkpoint.into();
+    let config = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    let diagnostics = serde_json::to_string_pretty(&diagnostics).map_err(|e| e.to_string())?;
 
-    Err("Server did not become healthy within 15 seconds".to_string())
+    let dest_path = dest.clone();
+    let size = tauri::async_runtime::spawn_blocking(move || {
+        let copy = db::temp_db_path();
+        db::copy_db(&db_path, &copy)?;
+        let result = logs::export_zip(
+            std::path::Path::new(&dest_path),
+            &[
+                logs::Entry::File("workflows.db", std::path::Path::new(&copy)),
+                logs::Entry::Text("config.json", config),
+                logs::Entry::Text("diagnostics.json", diagnostics),
+            ],
+        );
+        db::remove_db_files(&copy);
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    desktop_log!("Captured state snapshot to {dest}");
+    Ok(serde_json::json!({ "written": true, "path": dest, "size": size }))
 }
 
+/// Move the sidecar to `port`: validate it, persist it, and restart on it.
 #[tauri::command]
-async fn stop_server(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+async fn set_port(app: tauri::AppHandle, port: u16) -> Result<serde_json::Value, String> {
+    server::check_port_range(port)?;
     let state = app.state::<SidecarState>();
-    let mut guard = state.0.lock().map_err(|e| e.to_string())?;
-    if let Some(child) = guard.take() {
-        child.kill().map_err(|e| format!("Failed to kill sidecar: {e}"))?;
+    if state.server().port == port {
+        return Ok(serde_json::json!({ "success": true, "port": port }));
+    }
+    if !server::is_port_free(port) {
+        let probe = ServerConfig {
+            port,
+            ..ServerConfig::default()
+        };
+        let is_caw = http_client(&app)
+            .get(probe.health_url())
+            .timeout(std::time::Duration::from_secs(1))
+            .send()
+            .await
+            .is_ok_and(|resp| resp.status().is_success());
+        return Err(if is_caw {
+            format!("Port {port} is already used by another caw server")
+        } else {
+            format!("Port {port} is in use by another process")
+        });
+    }
+
+    // Stop on the old port before switching, so the restart frees it cleanly
+    let _drain = drain_requests(&app).await?;
+    if let Some(sidecar) = take_sidecar(&app)? {
+        process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await;
+    }
+    state.server.lock().map_err(|e| e.to_string())?.port = port;
+    if let Ok(mut source) = app.state::<ProjectState>().port_source.lock() {
+        *source = project::Source::Settings;
     }
-    Ok(serde_json::json!({ "success": true }))
+    {
+        let settings_state = app.state::<SettingsState>();
+        let mut settings = settings_state.0.lock().map_err(|e| e.to_string())?;
+        settings.port = Some(port);
+        settings::save(&settings)?;
+    }
+
+    let mut result = restart_sidecar(&app).await?;
+    let _ = app.emit(
+        "server://port-changed",
+        serde_json::json!({ "port": port, "base_url": state.server().base_url() }),
+    );
+    result["port"] = port.into();
+    Ok(result)
+}
+
+/// Gracefully stop the sidecar: SIGTERM, then a hard kill if it hasn't exited in time.
+#[tauri::command]
+async fn stop_server(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
+    let outcome = match take_sidecar(&app)? {
+        Some(sidecar) => Some(process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await),
+        None => None,
+    };
+    Ok(stop_result(outcome))
+}
+
+/// Escape hatch for a wedged sidecar: `force` skips SIGTERM and kills immediately.
+#[tauri::command]
+async fn kill_server(app: tauri::AppHandle, force: bool) -> Result<serde_json::Value, String> {
+    let outcome = match take_sidecar(&app)? {
+        Some(sidecar) if force => Some(process::force_kill(sidecar).await),
+        Some(sidecar) => Some(process::stop_gracefully(sidecar, process::GRACEFUL_STOP_TIMEOUT).await),
+        None => None,
+    };
+    Ok(stop_result(outcome))
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // Must come first: a second launch exits here, before setup spawns a sidecar
+        .plugin(tauri_plugin_single_instance::init(on_second_instance))
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_window_state::Builder::new().build())
-        .invoke_handler(tauri::generate_handler![server_status, restart_server, stop_server])
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
+        .invoke_handler(tauri::generate_handler![
+            server_status,
+            server_port,
+            pause_supervision,
+            resume_supervision,
+            restart_server,
+            restart_all_servers,
+            server_capabilities,
+            safe_mode_status,
+            startup_metrics,
+            get_env_report,
+            set_use_system_proxy,
+            list_caw_processes,
+            kill_caw_process,
+            reset_settings,
+            toggle_unread_filter,
+            get_unread_filter,
+            stop_server,
+            kill_server,
+            get_db_mode,
+            set_db_mode,
+            reresolve_db,
+            workspace_changed,
+            set_theme,
+            set_activation_policy,
+            set_chrome_mode,
+            set_traffic_lights,
+            apply_appearance,
+            set_hang_watchdog,
+            set_request_drain_timeout,
+            set_prevent_app_nap,
+            subscribe_events,
+            benchmark_server,
+            get_config,
+            can_write_db,
+            db_path_provenance,
+            set_default_db,
+            set_notifications_enabled,
+            server_health_detail,
+            set_startup_timeout,
+            set_reuse_existing_server,
+            run_migrations,
+            check_db_integrity,
+            open_database,
+            server_resource_usage,
+            set_status_timeout,
+            copy_diagnostics,
+            current_db_path,
+            unsubscribe_events,
+            open_settings,
+            open_help,
+            batch_operation,
+            db_size_history,
+            db_disk_space,
+            health_history,
+            verify_db_consistency,
+            use_memory_db,
+            persist_memory_db,
+            rename_database,
+            migrate_db,
+            api_request,
+            open_api_in_browser,
+            get_auto_restart,
+            set_auto_restart,
+            collect_diagnostics,
+            export_logs,
+            capture_snapshot,
+            set_port,
+            repair_lock,
+            server_logs,
+            server_logs_filtered,
+            verify_sidecar_integrity,
+            open_sidecar_in_terminal,
+            sidecar_command_line
+        ])
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::ThemeChanged(theme) => on_theme_changed(window, *theme),
+            tauri::WindowEvent::Focused(focused) => on_focus_changed(window, *focused),
+            tauri::WindowEvent::Resized(_) => on_resized(window),
+            _ => {}
+        })
         .setup(|app| {
+            let started = std::time::Instant::now();
+            if updates_configured(app.handle()) {
+                app.handle().plugin(tauri_plugin_updater::Builder::new().build())?;
+            }
+            let mut timings = StartupTimings::default();
+            let safe_mode = safe_mode_requested();
+            app.manage(SafeMode(safe_mode));
+            let settings = if safe_mode {
+                desktop_log!("Starting in safe mode: sidecar and saved settings are skipped");
+                settings::set_read_only();
+                DesktopSettings::default()
+            } else {
+                let (settings, ms) = timed("config", settings::load);
+                timings.config_ms = ms;
+                settings
+            };
+
             // Build native macOS menu bar
-            build_menu(app)?;
+            build_menu(app, settings.unread_only, settings.chrome_mode, &settings.recent_dbs)?;
+            build_tray(app)?;
+
+            let theme = settings.theme;
+            let always_on_top = settings.always_on_top;
+            let chrome_mode = settings.chrome_mode;
+            let traffic_lights = settings.traffic_lights;
+            let port = settings.port;
+            let reuse_existing_server = settings.reuse_existing_server;
+            appearance::apply_activation_policy(app.handle(), settings.activation_policy);
 
             // Spawn sidecar
-            let db_path = resolve_db_path();
-            let sidecar = app.shell().sidecar("caw").unwrap();
-            let (_rx, child) = sidecar
-                .args(["--server", "--transport", "http", "--port", "3100", "--db", &db_path])
-                .spawn()
-                .expect("failed to spawn caw sidecar");
+            let ((mut db, _), git_ms) = timed("git", || {
+                resolve_db_path(settings.db_path.as_deref(), settings.last_db_path.as_deref())
+            });
+            timings.git_ms = git_ms;
+            let client = build_http_client(settings.use_system_proxy)?;
+            app.manage(SettingsState(Mutex::new(settings)));
+            app.manage(Activity::new());
+            app.manage(Supervision::default());
+            app.manage(DbWatch::default());
+            app.manage(RequestGate::default());
+            app.manage(HttpClient(Mutex::new(client)));
+            fall_back_if_readonly(app.handle(), &mut db);
 
-            app.manage(SidecarState(std::sync::Mutex::new(Some(child))));
+            let (git_root, git_ms) = timed("git", db::git_root);
+            timings.git_ms += git_ms;
+            // Project config sits under desktop settings but over defaults
+            let project = git_root.as_deref().and_then(project::load);
+            app.manage(WorkspaceState { git_root });
+            app.manage(Reresolve(tokio::sync::Mutex::new(None)));
+            let project_config = project.as_ref().map(|p| p.config.clone()).unwrap_or_default();
+            let (mut port, mut port_source) =
+                match (server::env_port(), port, project_config.port) {
+                    (Some(port), _, _) => (port, project::Source::Env),
+                    (None, Some(port), _) => (port, project::Source::Settings),
+                    (None, None, Some(port)) => (port, project::Source::Project),
+                    (None, None, None) => (server::DEFAULT_PORT, project::Source::Default),
+                };
+            let takeover = if safe_mode {
+                Takeover::None
+            } else {
+                take_over_stale_server(port, &db.path, reuse_existing_server)
+            };
+            // Another service on the port would leave the app with no backend
+            match server::find_free_port(port) {
+                // A killed server's port is waited for by `spawn_after_takeover`
+                _ if takeover != Takeover::None => {}
+                Some(free) if !safe_mode && free != port => {
+                    // For this run only; the configured port is tried again next launch
+                    desktop_log!("Port {port} is in use; starting the sidecar on {free}");
+                    port = free;
+                    port_source = project::Source::Session;
+                }
+                None if !safe_mode => desktop_log!("Warning: no free port found from {port}"),
+                _ => {}
+            }
+            let transport = project_config.transport.as_deref().and_then(|mode| {
+                match server::Transport::parse(mode) {
+                    Ok(transport) => Some(transport),
+                    Err(e) => {
+                        desktop_log!("Warning: ignoring project transport: {e}");
+                        None
+                    }
+                }
+            });
+            let transport_source = match transport {
+                Some(_) => project::Source::Project,
+                None => project::Source::Default,
+            };
+            let server = ServerConfig {
+                port,
+                transport: transport.unwrap_or_default(),
+                extra_args: project_config.extra_args,
+            };
+            app.manage(ProjectState {
+                loaded: project,
+                port_source: Mutex::new(port_source),
+                transport_source: Mutex::new(transport_source),
+            });
+            app.manage(SidecarState {
+                sidecar: Mutex::new(None),
+                db: Mutex::new(db.clone()),
+                spawned_db: Mutex::new(None),
+                server: Mutex::new(server.clone()),
+                shutting_down: AtomicBool::new(false),
+                started: AtomicBool::new(false),
+                capabilities: Mutex::new(None),
+                stderr_tail: logs::Tail::new(STDERR_TAIL_LINES),
+                output_tail: logs::Tail::new(OUTPUT_TAIL_LINES),
+                last_crash: Mutex::new(None),
+                crash_restarts: process::CrashRestarts::default(),
+                spawn_error: Mutex::new(None),
+                restarting: process::RestartFlag::default(),
+            });
+            if let Takeover::Adopted(pid) = takeover {
+                let state = app.state::<SidecarState>();
+                *state.sidecar.lock().unwrap_or_else(PoisonError::into_inner) =
+                    Some(Sidecar::Adopted(pid));
+                track_sidecar(app.handle(), &db.path, pid);
+            } else if !safe_mode && takeover == Takeover::None {
+                warn_if_sidecar_modified(app.handle());
+                // Hold the lock while spawning so the crash watcher sees the child
+                let state = app.state::<SidecarState>();
+                let mut guard = state.sidecar.lock().unwrap_or_else(PoisonError::into_inner);
+                let (child, spawn_ms) = timed("spawn", || spawn_sidecar(app.handle(), &db.path));
+                timings.spawn_ms = spawn_ms;
+                // Without a sidecar the window still opens, showing the spawn
+                // error; `restart_server` retries
+                *guard = child.ok().map(Sidecar::Child);
+            }
 
             // Show window immediately — don't gate on sidecar health
             if let Some(window) = app.get_webview_window("main") {
-                #[cfg(target_os = "macos")]
-                {
-                    let _ = apply_vibrancy(
-                        &window,
-                        NSVisualEffectMaterial::Sidebar,
-                        None,
-                        None,
-                    );
-                    set_traffic_light_position(&window, 14.0, 18.0);
+                if !safe_mode {
+                    // A failure here must never keep the window from showing
+                    let (customized, ms) = timed("appearance", || {
+                        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            appearance::apply_theme(&window, theme);
+                            if always_on_top {
+                                let _ = window.set_always_on_top(true);
+                            }
+                            apply_chrome_mode(&window, chrome_mode, traffic_lights);
+                        }))
+                    });
+                    if customized.is_err() {
+                        desktop_log!("Warning: window appearance setup panicked; continuing");
+                    }
+                    timings.appearance_ms = ms;
+                }
+                let ((), ms) = timed("window", || {
+                    if let Err(e) = window.show() {
+                        desktop_log!("Warning: failed to show main window: {e}");
+                    }
+                });
+                timings.window_ms = ms;
+            }
+            app.manage(StartupMetrics::new(started, timings));
+
+            // Sample database size periodically for db_size_history
+            app.manage(SizeHistory::load());
+            let handle = app.handle().clone();
+            let sampler = tauri::async_runtime::spawn(async move {
+                loop {
+                    if let Ok(db_path) = handle.state::<SidecarState>().db_path() {
+                        handle.state::<SizeHistory>().record(stats::sample(&db_path));
+                    }
+                    tokio::time::sleep(stats::SAMPLE_INTERVAL).await;
+                }
+            });
+            let handle = app.handle().clone();
+            let idle_timer = tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(idle::CHECK_INTERVAL).await;
+                    stop_if_idle(&handle).await;
+                }
+            });
+            app.manage(HealthHistory::default());
+            app.manage(Liveness(Mutex::new(None)));
+            let handle = app.handle().clone();
+            let health_monitor = tauri::async_runtime::spawn(async move {
+                let mut hang = HangDetector::default();
+                loop {
+                    let focused = handle
+                        .webview_windows()
+                        .values()
+                        .any(|window| window.is_focused().unwrap_or(false));
+                    let interval = if focused {
+                        health::MONITOR_INTERVAL
+                    } else {
+                        health::MONITOR_INTERVAL_BLURRED
+                    };
+                    tokio::time::sleep(interval).await;
+
+                    if handle.state::<Supervision>().is_paused() {
+                        hang.reset();
+                        continue;
+                    }
+                    // Deliberate stops (idle, stop_server) aren't outages
+                    let state = handle.state::<SidecarState>();
+                    let Some(pid) = state.pid() else {
+                        hang.reset();
+                        report_liveness(&handle, false);
+                        continue;
+                    };
+                    let url = state.server().health_url();
+                    let history = handle.state::<HealthHistory>();
+                    let sample = health::sample_health(&http_client(&handle), &url, &history).await;
+                    report_liveness(&handle, sample.ok);
+
+                    let (threshold, window) = handle
+                        .state::<SettingsState>()
+                        .0
+                        .lock()
+                        .map(|s| (s.hang_timeouts, s.hang_window_secs))
+                        .unwrap_or((0, 0));
+                    let window = std::time::Duration::from_secs(window);
+                    if hang.observe(&sample, threshold, window) {
+                        hang.reset();
+                        restart_if_hung(&handle, pid, threshold, window).await;
+                    }
+                }
+            });
+            let handle = app.handle().clone();
+            let notifier = tauri::async_runtime::spawn(async move {
+                let mut watch = notify::WorkflowWatch::default();
+                loop {
+                    tokio::time::sleep(notify::POLL_INTERVAL).await;
+                    check_finished_workflows(&handle, &mut watch).await;
+                }
+            });
+            app.manage(BackgroundTasks(Mutex::new(vec![
+                sampler,
+                idle_timer,
+                health_monitor,
+                notifier,
+            ])));
+            app.manage(EventStream(Mutex::new(None)));
+
+            // caw:// links: one the app was launched with, then any that arrive later
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                desktop_log!("Warning: could not register the {} scheme: {e}", deeplink::SCHEME);
+            }
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    open_deep_link(app.handle(), url.as_str());
                 }
-                let _ = window.show();
+            }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    open_deep_link(&handle, url.as_str());
+                }
+            });
+
+            let spawn_failed = app
+                .state::<SidecarState>()
+                .spawn_error
+                .lock()
+                .is_ok_and(|e| e.is_some());
+            if safe_mode || spawn_failed {
+                return Ok(());
             }
 
             // Log sidecar readiness in the background
+            let handle = app.handle().clone();
+            let config = startup_health_config(&handle);
             tauri::async_runtime::spawn(async move {
-                let client = reqwest::Client::new();
-                for _ in 0..60 {
-                    if let Ok(resp) = client.get("http://localhost:3100/health").send().await {
-                        if resp.status().is_success() {
-                            eprintln!("Sidecar ready on port 3100");
-                            return;
+                if takeover == Takeover::Killed && !spawn_after_takeover(&handle, port).await {
+                    return;
+                }
+                // The takeover may have moved the sidecar to another port
+                let server = handle.state::<SidecarState>().server();
+                let client = http_client(&handle);
+                let first_health = tracing::info_span!("startup", phase = "first_health");
+                let ready = poll_health_until_ready(&client, &server.health_url(), &config)
+                    .instrument(first_health)
+                    .await;
+                match ready {
+                    Ok(()) => {
+                        desktop_log!("Sidecar ready on port {}", server.port);
+                        report_liveness(&handle, true);
+                        let metrics = handle.state::<StartupMetrics>();
+                        if let Ok(mut timings) = metrics.timings.lock() {
+                            timings.first_health_ms =
+                                Some(metrics.started.elapsed().as_millis() as u64);
+                        }
+                        desktop_log!("{}", metrics.snapshot().summary());
+                        check_sidecar_version(&handle).await;
+                        let prewarm = handle
+                            .state::<SettingsState>()
+                            .0
+                            .lock()
+                            .is_ok_and(|s| s.prewarm_db);
+                        if prewarm {
+                            tauri::async_runtime::spawn(warm_database(handle, server));
                         }
                     }
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    Err(failure) => {
+                        let message = emit_health_timeout(&handle, &config, failure);
+                        desktop_log!("Warning: {message}");
+                    }
                 }
-                eprintln!("Warning: sidecar health check timed out");
             });
 
             Ok(())
@@ -203,34 +3008,468 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app, event| {
+            // Clicking a notification activates the app; bring the main window back
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Reopen { .. } = event {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.unminimize();
+                    let _ = window.set_focus();
+                }
+            }
             if let tauri::RunEvent::ExitRequested { .. } = event {
-                if let Some(state) = app.try_state::<SidecarState>() {
-                    if let Ok(mut guard) = state.0.lock() {
-                        if let Some(child) = guard.take() {
-                            let _ = child.kill();
-                        }
+                shut_down(app);
+            }
+        });
+}
+
+/// Whether this build has an update endpoint configured (`plugins.updater`
+/// in `tauri.conf.json`); the updater plugin is only registered if so.
+fn updates_configured(app: &tauri::AppHandle) -> bool {
+    app.config().plugins.0.contains_key("updater")
+}
+
+/// Show a one-button message dialog without waiting for it to close.
+fn show_message(app: &tauri::AppHandle, kind: MessageDialogKind, title: &str, message: String) {
+    app.dialog().message(message).title(title).kind(kind).show(|_| {});
+}
+
+/// Check the update endpoint. If there is a newer version, offer to install
+/// it; on yes, download and install it, then stop the sidecar the same way
+/// quitting does and relaunch. Otherwise say the app is up to date.
+async fn check_for_updates(app: tauri::AppHandle) {
+    let current = app.package_info().version.to_string();
+    // `updater_builder()` needs the plugin, which is only registered when configured
+    let update = if !updates_configured(&app) {
+        Err("Updates are not configured for this build".to_string())
+    } else {
+        // The Windows installer exits the app itself once it has unpacked
+        let handle = app.clone();
+        let updater = app
+            .updater_builder()
+            .on_before_exit(move || shut_down(&handle))
+            .build();
+        match updater {
+            Ok(updater) => updater.check().await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    };
+    let update = match update {
+        Ok(Some(update)) => update,
+        Ok(None) => {
+            let message = format!("caw {current} is the latest version.");
+            show_message(&app, MessageDialogKind::Info, "No Updates", message);
+            return;
+        }
+        Err(e) => {
+            desktop_log!("Update check failed: {e}");
+            let message = format!("Could not check for updates: {e}");
+            show_message(&app, MessageDialogKind::Error, "Update Check Failed", message);
+            return;
+        }
+    };
+
+    let prompt = format!(
+        "caw {} is available (you have {current}). Install it and restart now?",
+        update.version
+    );
+    let dialog = app.dialog().clone();
+    let install = tauri::async_runtime::spawn_blocking(move || {
+        dialog
+            .message(prompt)
+            .title("Update Available")
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Install and Restart".to_string(),
+                "Later".to_string(),
+            ))
+            .blocking_show()
+    })
+    .await
+    .unwrap_or(false);
+    if !install {
+        return;
+    }
+
+    desktop_log!("Downloading update {}", update.version);
+    let bytes = match update.download(|_, _| {}, || {}).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            desktop_log!("Update download failed: {e}");
+            let message = format!("Could not download the update: {e}");
+            show_message(&app, MessageDialogKind::Error, "Update Failed", message);
+            return;
+        }
+    };
+    // Install before stopping anything, so a failed install leaves the app running
+    if let Err(e) = update.install(bytes) {
+        desktop_log!("Update install failed: {e}");
+        let message = format!("Could not install the update: {e}");
+        show_message(&app, MessageDialogKind::Error, "Update Failed", message);
+        return;
+    }
+    let handle = app.clone();
+    let _ = tauri::async_runtime::spawn_blocking(move || shut_down(&handle)).await;
+    app.restart();
+}
+
+/// Stop background work and the sidecar before the process goes away:
+/// checkpoint, stop it gracefully, and clean up its PID file and any
+/// temporary database. Runs on exit and before an update relaunches the app.
+fn shut_down(app: &tauri::AppHandle) {
+    if let Some(tasks) = app.try_state::<BackgroundTasks>() {
+        if let Ok(mut tasks) = tasks.0.lock() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
+        }
+    }
+    if let Some(stream) = app.try_state::<EventStream>() {
+        if let Some(task) = stream.0.lock().ok().and_then(|mut task| task.take()) {
+            task.abort();
+        }
+    }
+    if let Some(state) = app.try_state::<SidecarState>() {
+        let server = state.server();
+        let caps = state
+            .capabilities
+            .lock()
+            .ok()
+            .and_then(|caps| caps.as_ref().map(|(_, caps)| caps.clone()));
+        if let Ok(mut guard) = state.sidecar.lock() {
+            state.shutting_down.store(true, Ordering::SeqCst);
+            if let Some(sidecar) = guard.take() {
+                checkpoint_before_exit(&server, caps.as_ref());
+                let method = process::stop_gracefully_blocking(sidecar, process::EXIT_STOP_TIMEOUT);
+                desktop_log!("Sidecar stopped on exit ({method:?})");
+            }
+        }
+        if let Some(db) = state.spawned_db.lock().ok().and_then(|db| db.clone()) {
+            process::remove_pid_file(&db);
+        }
+        if let Ok(db) = state.db.lock() {
+            if db.mode == DbMode::Temporary {
+                db::remove_db_files(&db.path);
+            }
+        }
+    }
+}
+
+/// The Messages ▸ Show Unread Only item, kept so its check mark can follow
+/// toggles that come from the frontend.
+struct UnreadMenuItem(CheckMenuItem<tauri::Wry>);
+
+/// Flip the global unread-only inbox filter, persist it, keep the menu check
+/// in sync, and tell the frontend.
+fn toggle_unread(app: &tauri::AppHandle) -> Result<bool, String> {
+    let unread_only = {
+        let state = app.state::<SettingsState>();
+        let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+        settings.unread_only = !settings.unread_only;
+        settings::save(&settings)?;
+        settings.unread_only
+    };
+
+    if let Some(item) = app.try_state::<UnreadMenuItem>() {
+        let _ = item.0.set_checked(unread_only);
+    }
+    let _ = app.emit(
+        "messages://unread-filter",
+        serde_json::json!({ "unread_only": unread_only }),
+    );
+    Ok(unread_only)
+}
+
+#[tauri::command]
+fn toggle_unread_filter(app: tauri::AppHandle) -> Result<bool, String> {
+    toggle_unread(&app)
+}
+
+#[tauri::command]
+fn get_unread_filter(app: tauri::AppHandle) -> Result<bool, String> {
+    let state = app.state::<SettingsState>();
+    let settings = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(settings.unread_only)
+}
+
+/// The disabled Help ▸ Database item showing the sidecar's database.
+struct DbMenuItem(MenuItem<tauri::Wry>);
+
+/// Longest database path shown in the menu before the front is elided.
+const DB_MENU_PATH_CHARS: usize = 48;
+
+fn db_menu_label(db_path: &str) -> String {
+    format!("Database: {}", menu_path(db_path))
+}
+
+/// `db_path` with the home directory as `~`, elided at the front when long.
+fn menu_path(db_path: &str) -> String {
+    let path = match std::env::var("HOME") {
+        Ok(home) if !home.is_empty() => db_path.replacen(&home, "~", 1),
+        _ => db_path.to_string(),
+    };
+    let chars = path.chars().count();
+    if chars <= DB_MENU_PATH_CHARS {
+        return path;
+    }
+    let tail: String = path.chars().skip(chars - DB_MENU_PATH_CHARS).collect();
+    format!("…{tail}")
+}
+
+/// How many databases File ▸ Recent Databases remembers.
+const RECENT_DBS_LIMIT: usize = 5;
+
+/// The File ▸ Recent Databases submenu, rebuilt whenever the list changes.
+struct RecentDbsMenu(Submenu<tauri::Wry>);
+
+/// Replace the submenu's items with one `recent-db:<index>` item per path.
+fn fill_recent_dbs_menu(
+    app: &tauri::AppHandle,
+    submenu: &Submenu<tauri::Wry>,
+    recent: &[String],
+) -> tauri::Result<()> {
+    for item in submenu.items()? {
+        submenu.remove(item.as_ref())?;
+    }
+    if recent.is_empty() {
+        let empty = MenuItemBuilder::with_id("recent-db:none", "No Recent Databases")
+            .enabled(false)
+            .build(app)?;
+        return submenu.append(&empty);
+    }
+    for (index, path) in recent.iter().enumerate() {
+        let item = MenuItemBuilder::with_id(format!("recent-db:{index}"), menu_path(path))
+            .build(app)?;
+        submenu.append(&item)?;
+    }
+    Ok(())
+}
+
+/// Switch to the recent database behind a `recent-db:<index>` menu item.
+fn open_recent_db(app: &tauri::AppHandle, index: usize) {
+    let path = app
+        .state::<SettingsState>()
+        .0
+        .lock()
+        .ok()
+        .and_then(|s| s.recent_dbs.get(index).cloned());
+    let Some(path) = path else {
+        return;
+    };
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = switch_to_db(&handle, path.clone()).await {
+            desktop_log!("Failed to open {path}: {e}");
+        }
+    });
+}
+
+/// The View ▸ Minimal Window item, kept so its check mark follows
+/// `set_chrome_mode` calls from the frontend.
+struct ChromeMenuItem(CheckMenuItem<tauri::Wry>);
+
+/// Apply `mode` to every window, persist it, and keep the menu check in sync.
+fn set_chrome(app: &tauri::AppHandle, mode: ChromeMode) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    for window in app.webview_windows().values() {
+        apply_chrome_mode(window, mode, settings.traffic_lights);
+    }
+    if let Some(item) = app.try_state::<ChromeMenuItem>() {
+        let _ = item.0.set_checked(mode == ChromeMode::Minimal);
+    }
+    let _ = app.emit("window://chrome-mode", serde_json::json!({ "mode": mode }));
+
+    settings.chrome_mode = mode;
+    settings::save(&settings)
+}
+
+/// Move the macOS traffic lights to (`x`, `y`) points from the top-left
+/// corner, live, and persist the inset. A no-op elsewhere, so the frontend
+/// can call it unconditionally.
+#[tauri::command]
+fn set_traffic_lights(app: tauri::AppHandle, x: f64, y: f64) -> Result<(), String> {
+    if !cfg!(target_os = "macos") {
+        return Ok(());
+    }
+    if !(0.0..=100.0).contains(&x) || !(0.0..=100.0).contains(&y) {
+        return Err("Traffic light offsets must be between 0 and 100".to_string());
+    }
+    let lights = TrafficLights { x, y };
+    let state = app.state::<SettingsState>();
+    let mut settings = state.0.lock().map_err(|e| e.to_string())?;
+    for window in app.webview_windows().values() {
+        apply_chrome_mode(window, settings.chrome_mode, lights);
+    }
+    settings.traffic_lights = lights;
+    settings::save(&settings)
+}
+
+/// Switch between `"normal"` and `"minimal"` window chrome and persist it.
+/// View ▸ Minimal Window (CmdOrCtrl+Shift+M) always switches back.
+#[tauri::command]
+fn set_chrome_mode(app: tauri::AppHandle, mode: String) -> Result<(), String> {
+    set_chrome(&app, ChromeMode::parse(&mode)?)
+}
+
+/// Focus the window labelled `label`, or create it at `url` if it isn't open.
+fn focus_or_create_window(
+    app: &tauri::AppHandle,
+    label: &str,
+    url: &str,
+    title: &str,
+) -> tauri::Result<()> {
+    if let Some(win) = app.get_webview_window(label) {
+        return win.set_focus();
+    }
+    WebviewWindowBuilder::new(app, label, WebviewUrl::App(url.into()))
+        .title(title)
+        .inner_size(700.0, 600.0)
+        .min_inner_size(500.0, 400.0)
+        .build()
+        .map(|_| ())
+}
+
+fn open_settings_window(app: &tauri::AppHandle) -> tauri::Result<()> {
+    focus_or_create_window(app, "settings", "/settings", "Settings")
+}
+
+fn open_help_window(app: &tauri::AppHandle) -> tauri::Result<()> {
+    focus_or_create_window(app, "help", "/help", "caw Help")
+}
+
+/// Open (or focus) the settings window, for in-app buttons on platforms
+/// where the menu bar is less prominent.
+#[tauri::command]
+fn open_settings(app: tauri::AppHandle) -> Result<(), String> {
+    open_settings_window(&app).map_err(|e| e.to_string())
+}
+
+/// The tray icon and its status line, which follow sidecar liveness.
+struct Tray {
+    icon: TrayIcon<tauri::Wry>,
+    status: MenuItem<tauri::Wry>,
+}
+
+impl Tray {
+    fn show_status(&self, running: bool) {
+        let (dot, status) = if running { ("●", "running") } else { ("○", "stopped") };
+        let _ = self.status.set_text(format!("{dot} Server {status}"));
+        let _ = self.icon.set_tooltip(Some(format!("caw — server {status}")));
+    }
+}
+
+/// Show the main window and focus it, or hide it if it is already visible.
+fn toggle_main_window(app: &tauri::AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Add the tray (menu bar) icon with quick actions that work without
+/// focusing the main window.
+fn build_tray(app: &mut tauri::App) -> tauri::Result<()> {
+    let handle = app.handle();
+    let status_item = MenuItemBuilder::with_id("tray_status", "○ Server starting…")
+        .enabled(false)
+        .build(handle)?;
+    let menu = MenuBuilder::new(handle)
+        .item(&status_item)
+        .separator()
+        .item(&MenuItemBuilder::with_id("tray_toggle_window", "Show/Hide caw").build(handle)?)
+        .item(&MenuItemBuilder::with_id("tray_restart_server", "Restart Server").build(handle)?)
+        .item(&MenuItemBuilder::with_id("tray_stop_server", "Stop Server").build(handle)?)
+        .separator()
+        .item(&PredefinedMenuItem::quit(handle, Some("Quit caw"))?)
+        .build()?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .menu(&menu)
+        .tooltip("caw")
+        .on_menu_event(|app, event| match event.id().0.as_str() {
+            "tray_toggle_window" => toggle_main_window(app),
+            "tray_restart_server" => {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = restart_server(handle, None).await {
+                        desktop_log!("Tray restart failed: {}", e.message);
                     }
-                }
+                });
+            }
+            "tray_stop_server" => {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = stop_server(handle).await {
+                        desktop_log!("Tray stop failed: {e}");
+                    }
+                });
             }
+            _ => {}
         });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let icon = builder.build(handle)?;
+    app.manage(Tray {
+        icon,
+        status: status_item,
+    });
+    Ok(())
+}
+
+/// Open (or focus) the help window.
+#[tauri::command]
+fn open_help(app: tauri::AppHandle) -> Result<(), String> {
+    open_help_window(&app).map_err(|e| e.to_string())
 }
 
-fn build_menu(app: &mut tauri::App) -> tauri::Result<()> {
+fn build_menu(
+    app: &mut tauri::App,
+    unread_only: bool,
+    chrome_mode: ChromeMode,
+    recent_dbs: &[String],
+) -> tauri::Result<()> {
     let handle = app.handle();
 
     // App submenu
     let settings_item = MenuItemBuilder::with_id("settings", "Settings…")
         .accelerator("CmdOrCtrl+,")
         .build(handle)?;
+    let updates_item =
+        MenuItemBuilder::with_id("check_for_updates", "Check for Updates…").build(handle)?;
 
-    let app_submenu = SubmenuBuilder::new(handle, "caw")
-        .item(&PredefinedMenuItem::about(handle, Some("About caw"), None)?)
+    let about_item = PredefinedMenuItem::about(handle, Some("About caw"), None)?;
+    let mut app_submenu = SubmenuBuilder::new(handle, "caw").item(&about_item);
+    // Builds without an update endpoint have nothing to check against
+    if updates_configured(handle) {
+        app_submenu = app_submenu.item(&updates_item);
+    }
+    let app_submenu = app_submenu
         .separator()
         .item(&settings_item)
         .separator()
         .item(&PredefinedMenuItem::quit(handle, Some("Quit caw"))?)
         .build()?;
 
+    // File submenu
+    let open_db_item = MenuItemBuilder::with_id("open_database", "Open Database…")
+        .accelerator("CmdOrCtrl+O")
+        .build(handle)?;
+    let recent_submenu = SubmenuBuilder::new(handle, "Recent Databases").build()?;
+    fill_recent_dbs_menu(handle, &recent_submenu, recent_dbs)?;
+
+    let file_submenu = SubmenuBuilder::new(handle, "File")
+        .item(&open_db_item)
+        .item(&recent_submenu)
+        .build()?;
+    app.manage(RecentDbsMenu(recent_submenu));
+
     // Edit submenu
     let edit_submenu = SubmenuBuilder::new(handle, "Edit")
         .item(&PredefinedMenuItem::undo(handle, None)?)
@@ -250,11 +3489,30 @@ fn build_menu(app: &mut tauri::App) -> tauri::Result<()> {
         .accelerator("Ctrl+CmdOrCtrl+F")
         .build(handle)?;
 
+    // Stays reachable by shortcut even when minimal mode hides the menu bar
+    let minimal_item = CheckMenuItemBuilder::with_id("toggle_minimal_chrome", "Minimal Window")
+        .accelerator("CmdOrCtrl+Shift+M")
+        .checked(chrome_mode == ChromeMode::Minimal)
+        .build(handle)?;
+
     let view_submenu = SubmenuBuilder::new(handle, "View")
         .item(&reload_item)
         .separator()
         .item(&fullscreen_item)
+        .item(&minimal_item)
         .build()?;
+    app.manage(ChromeMenuItem(minimal_item.clone()));
+
+    // Messages submenu
+    let unread_item = CheckMenuItemBuilder::with_id("toggle_unread", "Show Unread Only")
+        .accelerator("CmdOrCtrl+Shift+U")
+        .checked(unread_only)
+        .build(handle)?;
+
+    let messages_submenu = SubmenuBuilder::new(handle, "Messages")
+        .item(&unread_item)
+        .build()?;
+    app.manage(UnreadMenuItem(unread_item.clone()));
 
     // Window submenu
     let window_submenu = SubmenuBuilder::new(handle, "Window")
@@ -267,18 +3525,49 @@ fn build_menu(app: &mut tauri::App) -> tauri::Result<()> {
     // Help submenu
     let help_item = MenuItemBuilder::with_id("help", "caw Help")
         .build(handle)?;
+    let db_item = MenuItemBuilder::with_id("db_path", "Database: not started")
+        .enabled(false)
+        .build(handle)?;
+
+    let diagnostics_item =
+        MenuItemBuilder::with_id("copy_diagnostics", "Copy Diagnostics").build(handle)?;
 
     let help_submenu = SubmenuBuilder::new(handle, "Help")
         .item(&help_item)
+        .item(&diagnostics_item)
+        .separator()
+        .item(&db_item)
         .build()?;
+    app.manage(DbMenuItem(db_item));
 
-    let menu = MenuBuilder::new(handle)
+    let menu_builder = MenuBuilder::new(handle)
         .item(&app_submenu)
+        .item(&file_submenu)
         .item(&edit_submenu)
         .item(&view_submenu)
-        .item(&window_submenu)
-        .item(&help_submenu)
-        .build()?;
+        .item(&messages_submenu)
+        .item(&window_submenu);
+
+    // Developer submenu (debug builds only)
+    #[cfg(debug_assertions)]
+    let menu_builder = {
+        let devtools_item = MenuItemBuilder::with_id("toggle_devtools", "Toggle Developer Tools")
+            .accelerator("CmdOrCtrl+Alt+I")
+            .build(handle)?;
+        let hard_reload_item =
+            MenuItemBuilder::with_id("reload_ignoring_cache", "Reload Ignoring Cache")
+                .accelerator("CmdOrCtrl+Shift+R")
+                .build(handle)?;
+
+        let developer_submenu = SubmenuBuilder::new(handle, "Developer")
+            .item(&devtools_item)
+            .item(&hard_reload_item)
+            .build()?;
+
+        menu_builder.item(&developer_submenu)
+    };
+
+    let menu = menu_builder.item(&help_submenu).build()?;
 
     app.set_menu(menu)?;
 
@@ -286,36 +3575,55 @@ fn build_menu(app: &mut tauri::App) -> tauri::Result<()> {
     let handle_clone = app.handle().clone();
     app.on_menu_event(move |_app, event| {
         let id = event.id().0.as_str();
+        if let Some(index) = id.strip_prefix("recent-db:").and_then(|i| i.parse().ok()) {
+            open_recent_db(&handle_clone, index);
+            return;
+        }
         if let Some(window) = handle_clone.get_webview_window("main") {
             match id {
                 "settings" => {
-                    if let Some(win) = handle_clone.get_webview_window("settings") {
-                        let _ = win.set_focus();
-                    } else {
-                        let _ = WebviewWindowBuilder::new(
-                            &handle_clone,
-                            "settings",
-                            WebviewUrl::App("/settings".into()),
-                        )
-                        .title("Settings")
-                        .inner_size(700.0, 600.0)
-                        .min_inner_size(500.0, 400.0)
-                        .build();
+                    if let Err(e) = open_settings_window(&handle_clone) {
+                        desktop_log!("Failed to open settings: {e}");
                     }
                 }
+                "check_for_updates" => {
+                    tauri::async_runtime::spawn(check_for_updates(handle_clone.clone()));
+                }
+                "open_database" => {
+                    let handle = handle_clone.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = open_database(handle).await {
+                            desktop_log!("Failed to open database: {e}");
+                        }
+                    });
+                }
+                "copy_diagnostics" => {
+                    let handle = handle_clone.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = copy_diagnostics(handle).await {
+                            desktop_log!("Failed to copy diagnostics: {e}");
+                        }
+                    });
+                }
                 "help" => {
-                    if let Some(win) = handle_clone.get_webview_window("settings") {
-                        let _ = win.set_focus();
-                    } else {
-                        let _ = WebviewWindowBuilder::new(
-                            &handle_clone,
-                            "settings",
-                            WebviewUrl::App("/settings".into()),
-                        )
-                        .title("Settings")
-                        .inner_size(700.0, 600.0)
-                        .min_inner_size(500.0, 400.0)
-                        .build();
+                    if let Err(e) = open_help_window(&handle_clone) {
+                        desktop_log!("Failed to open help: {e}");
+                    }
+                }
+                "toggle_unread" => {
+                    if let Err(e) = toggle_unread(&handle_clone) {
+                        desktop_log!("Failed to toggle unread filter: {e}");
+                    }
+                }
+                "toggle_minimal_chrome" => {
+                    let mode = match handle_clone.state::<SettingsState>().0.lock() {
+                        Ok(settings) if settings.chrome_mode == ChromeMode::Minimal => {
+                            ChromeMode::Normal
+                        }
+                        _ => ChromeMode::Minimal,
+                    };
+                    if let Err(e) = set_chrome(&handle_clone, mode) {
+                        desktop_log!("Failed to change window chrome: {e}");
                     }
                 }
                 "reload" => {
@@ -328,6 +3636,34 @@ fn build_menu(app: &mut tauri::App) -> tauri::Result<()> {
                         let _ = window.set_fullscreen(!is_fullscreen);
                     }
                 }
+                #[cfg(debug_assertions)]
+                "toggle_devtools" => {
+                    if window.is_devtools_open() {
+                        window.close_devtools();
+                    } else {
+                        window.open_devtools();
+                    }
+                }
+                #[cfg(debug_assertions)]
+                "reload_ignoring_cache" => {
+                    // Navigate with a cache-busting query param so assets are re-fetched
+                    if let Ok(mut url) = window.url() {
+                        let pairs: Vec<(String, String)> = url
+                            .query_pairs()
+                            .filter(|(k, _)| k != "_reload")
+                            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                            .collect();
+                        let stamp = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_millis())
+                            .unwrap_or_default();
+                        url.query_pairs_mut()
+                            .clear()
+                            .extend_pairs(pairs)
+                            .append_pair("_reload", &stamp.to_string());
+                        let _ = window.navigate(url);
+                    }
+                }
                 "zoom" => {
                     if let Ok(is_maximized) = window.is_maximized() {
                         if is_maximized {
@@ -0,0 +1,290 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+pub const DESKTOP_LOG: &str = "desktop.log";
+pub const SIDECAR_LOG: &str = "sidecar.log";
+
+/// Rotate a log once it grows past this size.
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated files (`<name>.1` … `<name>.N`) to keep.
+const ROTATIONS: usize = 3;
+
+/// Log to stderr and append to `~/.caw/logs/desktop.log`.
+macro_rules! desktop_log {
+    ($($arg:tt)*) => {{
+        let line = format!($($arg)*);
+        eprintln!("{line}");
+        $crate::logs::write_desktop(&line);
+    }};
+}
+pub(crate) use desktop_log;
+
+pub fn logs_dir() -> PathBuf {
+    crate::db::caw_home().join("logs")
+}
+
+/// The log file and its rotations, newest first, that currently exist.
+pub fn log_files(name: &str) -> Vec<PathBuf> {
+    let dir = logs_dir();
+    std::iter::once(dir.join(name))
+        .chain((1..=ROTATIONS).map(|i| dir.join(format!("{name}.{i}"))))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Line-oriented appender that rotates the file when it gets too large.
+pub struct Appender {
+    path: PathBuf,
+    file: File,
+    len: u64,
+}
+
+impl Appender {
+    pub fn open(name: &str) -> std::io::Result<Self> {
+        let dir = logs_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(name);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self { path, file, len })
+    }
+
+    pub fn write_line(&mut self, line: &str) {
+        if self.len >= MAX_LOG_BYTES {
+            self.rotate();
+        }
+        if writeln!(self.file, "{line}").is_ok() {
+            self.len += line.len() as u64 + 1;
+        }
+    }
+
+    fn rotate(&mut self) {
+        let rotated = |i: usize| {
+            let mut os = self.path.as_os_str().to_owned();
+            os.push(format!(".{i}"));
+            PathBuf::from(os)
+        };
+        for i in (1..ROTATIONS).rev() {
+            let _ = std::fs::rename(rotated(i), rotated(i + 1));
+        }
+        let _ = std::fs::rename(&self.path, rotated(1));
+        if let Ok(file) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+        {
+            self.file = file;
+            self.len = 0;
+        }
+    }
+}
+
+/// The last few lines of a stream, e.g. sidecar stderr, for error reports.
+pub struct Tail {
+    lines: Mutex<VecDeque<String>>,
+    cap: usize,
+}
+
+impl Tail {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            lines: Mutex::new(VecDeque::with_capacity(cap)),
+            cap,
+        }
+    }
+
+    pub fn push(&self, line: &str) {
+        if let Ok(mut lines) = self.lines.lock() {
+            if lines.len() == self.cap {
+                lines.pop_front();
+            }
+            lines.push_back(line.to_string());
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut lines) = self.lines.lock() {
+            lines.clear();
+        }
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+static DESKTOP: Mutex<Option<Appender>> = Mutex::new(None);
+
+pub fn write_desktop(line: &str) {
+    let Ok(mut guard) = DESKTOP.lock() else {
+        return;
+    };
+    if guard.is_none() {
+        *guard = Appender::open(DESKTOP_LOG).ok();
+    }
+    if let Some(appender) = guard.as_mut() {
+        appender.write_line(line);
+    }
+}
+
+/// Severity parsed from a log line's level token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn parse(level: &str) -> Result<Self, String> {
+        Self::from_token(level).ok_or_else(|| format!("Unknown log level: {level}"))
+    }
+
+    fn from_token(token: &str) -> Option<Self> {
+        match token.to_ascii_lowercase().as_str() {
+            "trace" | "debug" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warn" | "warning" => Some(Self::Warn),
+            "error" | "err" | "fatal" => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Find a level token near the start of a line, tolerating the usual shapes
+/// (`[WARN]`, `ERROR:`, `level=info`) and a leading timestamp.
+fn parse_level(line: &str) -> Option<Level> {
+    line.split_whitespace().take(3).find_map(|token| {
+        let token = token.strip_prefix("level=").unwrap_or(token);
+        Level::from_token(token.trim_matches(|c: char| !c.is_ascii_alphabetic()))
+    })
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct FilteredLogs {
+    /// Lines with a recognised level at or above the minimum.
+    pub lines: Vec<LogLine>,
+    /// Lines with no recognisable level; only the substring filter applies.
+    pub raw: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LogLine {
+    pub level: Level,
+    pub text: String,
+}
+
+/// The most recent `limit` lines of `name` (across rotations) that pass the
+/// filters, oldest first.
+pub fn tail_filtered(
+    name: &str,
+    limit: usize,
+    min_level: Option<Level>,
+    contains: Option<&str>,
+) -> FilteredLogs {
+    let mut logs = FilteredLogs::default();
+    let mut matched = 0;
+    'files: for path in log_files(name) {
+        let Ok(contents) = std::fs::read(&path) else {
+            continue;
+        };
+        for line in String::from_utf8_lossy(&contents).lines().rev() {
+            if matched >= limit {
+                break 'files;
+            }
+            if contains.is_some_and(|needle| !line.contains(needle)) {
+                continue;
+            }
+            match parse_level(line) {
+                Some(level) if min_level.is_some_and(|min| level < min) => continue,
+                Some(level) => logs.lines.push(LogLine {
+                    level,
+                    text: line.to_string(),
+                }),
+                None => logs.raw.push(line.to_string()),
+            }
+            matched += 1;
+        }
+    }
+    logs.lines.reverse();
+    logs.raw.reverse();
+    logs
+}
+
+/// Replace the user's home directory with `~`, and the values of secret-looking
+/// `CAW_*` variables (e.g. an auth token) with `[redacted]`, so exported files
+/// don't leak usernames, directory layout, or credentials.
+pub fn redact(text: &str) -> String {
+    let mut text = match std::env::var("HOME") {
+        Ok(home) if !home.is_empty() => text.replace(&home, "~"),
+        _ => text.to_string(),
+    };
+    for (name, value) in std::env::vars() {
+        if name.starts_with("CAW_") && crate::environment::looks_secret(&name) && value.len() >= 8
+        {
+            text = text.replace(&value, "[redacted]");
+        }
+    }
+    text
+}
+
+/// A file to add to an export archive.
+pub enum Entry<'a> {
+    /// Text, redacted on the way in.
+    Text(&'a str, String),
+    /// A file on disk stored byte-for-byte, e.g. a database copy.
+    File(&'a str, &'a Path),
+}
+
+/// Zip the desktop and sidecar logs (with rotations) plus `entries` into
+/// `dest`, redacting each text file on the way in. Returns the archive size.
+pub fn export_zip(dest: &Path, entries: &[Entry]) -> Result<u64, String> {
+    let file =
+        File::create(dest).map_err(|e| format!("Failed to create {}: {e}", dest.display()))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut add = |name: &str, contents: &mut dyn std::io::Read| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        std::io::copy(contents, &mut zip)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+
+    for path in log_files(DESKTOP_LOG)
+        .into_iter()
+        .chain(log_files(SIDECAR_LOG))
+    {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let contents = std::fs::read(&path).map_err(|e| e.to_string())?;
+        add(name, &mut redact(&String::from_utf8_lossy(&contents)).as_bytes())?;
+    }
+    for entry in entries {
+        match entry {
+            Entry::Text(name, text) => add(name, &mut redact(text).as_bytes())?,
+            Entry::File(name, path) => {
+                let mut file = File::open(path)
+                    .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+                add(name, &mut file)?;
+            }
+        }
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    std::fs::metadata(dest)
+        .map(|m| m.len())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,322 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Bookkeeping table owned by the sidecar's migration runner; never copied.
+const MIGRATIONS_TABLE: &str = "schema_migrations";
+/// Alphabet and length of the sidecar's generated IDs (`wf_…`, `tk_…`).
+const ID_ALPHABET: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const ID_LEN: usize = 12;
+
+#[derive(Debug, Default, Serialize)]
+pub struct TableReport {
+    /// Rows inserted into the destination, remapped or not.
+    pub copied: usize,
+    /// Rows already present in the destination (same row, or the same unique
+    /// key such as a repository path), so references point at the existing one.
+    pub merged: usize,
+    /// Rows whose ID collided with a different destination row and got a new one.
+    pub remapped: usize,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MigrateReport {
+    pub source: String,
+    pub destination: String,
+    /// The destination did not exist, so the source was copied wholesale.
+    pub created: bool,
+    pub tables: BTreeMap<String, TableReport>,
+}
+
+struct TableInfo {
+    name: String,
+    columns: Vec<String>,
+    /// Index of the primary key column, when the key is a single column.
+    pk: Option<usize>,
+    /// Foreign-key columns and the table they reference.
+    fks: Vec<(usize, String)>,
+    /// Column sets of `UNIQUE` constraints other than the primary key.
+    uniques: Vec<Vec<usize>>,
+}
+
+enum Action {
+    Insert,
+    Merge,
+    Remap,
+}
+
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+fn schema_version(conn: &Connection, schema: &str) -> Result<i64, String> {
+    conn.query_row(
+        &format!("SELECT COALESCE(MAX(version), 0) FROM {schema}.{MIGRATIONS_TABLE}"),
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Not a caw database ({schema}): {e}"))
+}
+
+fn table_info(conn: &Connection, name: &str) -> rusqlite::Result<TableInfo> {
+    let mut pks = Vec::new();
+    let mut columns = Vec::new();
+    let mut stmt = conn.prepare("SELECT name, pk FROM pragma_table_info(?1, 'main')")?;
+    for row in stmt.query_map([name], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))? {
+        let (column, pk) = row?;
+        if pk > 0 {
+            pks.push(columns.len());
+        }
+        columns.push(column);
+    }
+    let position = |column: &str| columns.iter().position(|c| c == column);
+
+    let mut fks = Vec::new();
+    let mut stmt =
+        conn.prepare("SELECT \"from\", \"table\" FROM pragma_foreign_key_list(?1, 'main')")?;
+    for row in stmt.query_map([name], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+        let (column, parent) = row?;
+        if let Some(index) = position(&column) {
+            fks.push((index, parent));
+        }
+    }
+
+    let mut uniques = Vec::new();
+    let mut stmt = conn.prepare(
+        "SELECT name FROM pragma_index_list(?1, 'main') WHERE \"unique\" = 1 AND origin = 'u'",
+    )?;
+    let indexes: Vec<String> = stmt
+        .query_map([name], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    for index in indexes {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_index_info(?1, 'main')")?;
+        let cols: Vec<String> = stmt
+            .query_map([&index], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        let cols: Option<Vec<usize>> = cols.iter().map(|c| position(c)).collect();
+        uniques.extend(cols);
+    }
+
+    Ok(TableInfo {
+        name: name.to_string(),
+        pk: (pks.len() == 1).then(|| pks[0]),
+        columns,
+        fks,
+        uniques,
+    })
+}
+
+fn read_rows(conn: &Connection, table: &TableInfo) -> rusqlite::Result<Vec<Vec<Value>>> {
+    let columns: Vec<String> = table.columns.iter().map(|c| quote(c)).collect();
+    let sql = format!("SELECT {} FROM src.{}", columns.join(", "), quote(&table.name));
+    let mut stmt = conn.prepare(&sql)?;
+    let width = table.columns.len();
+    let rows = stmt.query_map([], |row| (0..width).map(|i| row.get(i)).collect())?;
+    rows.collect()
+}
+
+/// The existing destination row with the same values in `cols`, if any.
+fn find_by(
+    conn: &Connection,
+    table: &TableInfo,
+    cols: &[usize],
+    row: &[Value],
+) -> rusqlite::Result<Option<Vec<Value>>> {
+    let filter: Vec<String> = cols
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| format!("{} IS ?{}", quote(&table.columns[c]), i + 1))
+        .collect();
+    let columns: Vec<String> = table.columns.iter().map(|c| quote(c)).collect();
+    let sql = format!(
+        "SELECT {} FROM main.{} WHERE {} LIMIT 1",
+        columns.join(", "),
+        quote(&table.name),
+        filter.join(" AND ")
+    );
+    let params: Vec<&Value> = cols.iter().map(|&c| &row[c]).collect();
+    let width = table.columns.len();
+    let mut stmt = conn.prepare(&sql)?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(params))?;
+    match rows.next()? {
+        Some(found) => Ok(Some((0..width).map(|i| found.get(i)).collect::<rusqlite::Result<_>>()?)),
+        None => Ok(None),
+    }
+}
+
+/// Whether `id` is already a primary key in either database's copy of `table`.
+fn id_taken(conn: &Connection, table: &TableInfo, id: &str) -> rusqlite::Result<bool> {
+    let Some(pk) = table.pk else {
+        return Ok(false);
+    };
+    let (name, column) = (quote(&table.name), quote(&table.columns[pk]));
+    conn.query_row(
+        &format!(
+            "SELECT EXISTS (SELECT 1 FROM main.{name} WHERE {column} = ?1) \
+             OR EXISTS (SELECT 1 FROM src.{name} WHERE {column} = ?1)"
+        ),
+        [id],
+        |row| row.get(0),
+    )
+}
+
+/// A fresh ID in the sidecar's format, keeping the original's prefix.
+fn new_id(old: &str) -> String {
+    let prefix = old.split_once('_').map_or("id", |(prefix, _)| prefix);
+    let suffix: String = (0..ID_LEN)
+        .map(|_| ID_ALPHABET[fastrand::usize(..ID_ALPHABET.len())] as char)
+        .collect();
+    format!("{prefix}_{suffix}")
+}
+
+/// Merge every row of the caw database at `src` into `dest`. Rows already in
+/// `dest` are reused, colliding IDs are remapped, and foreign-key references
+/// follow the remapping. Both databases must be on the same schema version.
+/// A missing `dest` is created as a straight copy of `src`.
+pub fn merge_into(src: &str, dest: &str) -> Result<MigrateReport, String> {
+    let mut report = MigrateReport {
+        source: src.to_string(),
+        destination: dest.to_string(),
+        ..MigrateReport::default()
+    };
+    if !Path::new(dest).exists() {
+        if let Some(dir) = Path::new(dest).parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Cannot create {}: {e}", dir.display()))?;
+        }
+        crate::db::copy_db(src, dest)?;
+        report.created = true;
+        return Ok(report);
+    }
+
+    let mut conn = Connection::open(dest).map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA foreign_keys = OFF")
+        .map_err(|e| e.to_string())?;
+    conn.execute("ATTACH DATABASE ?1 AS src", [src])
+        .map_err(|e| format!("Failed to open {src}: {e}"))?;
+    let (src_version, dest_version) = (schema_version(&conn, "src")?, schema_version(&conn, "main")?);
+    if src_version != dest_version {
+        return Err(format!(
+            "Schema versions differ (source v{src_version}, destination v{dest_version}); \
+             open both with the current caw first"
+        ));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let db_err = |e: rusqlite::Error| e.to_string();
+    let names: Vec<String> = tx
+        .prepare(
+            "SELECT m.name FROM pragma_table_list AS m \
+             JOIN src.sqlite_master AS s ON s.name = m.name AND s.type = 'table' \
+             WHERE m.schema = 'main' AND m.type = 'table' \
+             AND m.name NOT LIKE 'sqlite_%' AND m.name <> ?1 ORDER BY m.name",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map([MIGRATIONS_TABLE], |row| row.get(0))?
+                .collect()
+        })
+        .map_err(db_err)?;
+
+    // First pass: decide each row's fate so references can be remapped even
+    // across cycles (tasks ↔ agents) in the second.
+    let mut ids: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut plans = Vec::new();
+    for name in &names {
+        let table = table_info(&tx, name).map_err(db_err)?;
+        let mut rows = Vec::new();
+        for row in read_rows(&tx, &table).map_err(db_err)? {
+            let Some(pk) = table.pk else {
+                rows.push((row, Action::Insert));
+                continue;
+            };
+            let Value::Text(old) = &row[pk] else {
+                rows.push((row, Action::Insert));
+                continue;
+            };
+            let mut existing = None;
+            for cols in &table.uniques {
+                if let Some(found) = find_by(&tx, &table, cols, &row).map_err(db_err)? {
+                    existing = Some(found);
+                    break;
+                }
+            }
+            let action = match existing {
+                Some(found) => {
+                    if let Value::Text(id) = &found[pk] {
+                        ids.entry(name.clone()).or_default().insert(old.clone(), id.clone());
+                    }
+                    Action::Merge
+                }
+                None => match find_by(&tx, &table, &[pk], &row).map_err(db_err)? {
+                    Some(found) if found == row => Action::Merge,
+                    Some(_) => {
+                        let id = loop {
+                            let id = new_id(old);
+                            if !id_taken(&tx, &table, &id).map_err(db_err)? {
+                                break id;
+                            }
+                        };
+                        ids.entry(name.clone()).or_default().insert(old.clone(), id);
+                        Action::Remap
+                    }
+                    None => Action::Insert,
+                },
+            };
+            rows.push((row, action));
+        }
+        plans.push((table, rows));
+    }
+
+    // Second pass: insert with primary and foreign keys rewritten.
+    for (table, rows) in plans {
+        let entry = report.tables.entry(table.name.clone()).or_default();
+        let columns: Vec<String> = table.columns.iter().map(|c| quote(c)).collect();
+        let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{i}")).collect();
+        let verb = if table.pk.is_some() { "INSERT" } else { "INSERT OR IGNORE" };
+        let sql = format!(
+            "{verb} INTO main.{} ({}) VALUES ({})",
+            quote(&table.name),
+            columns.join(", "),
+            placeholders.join(", ")
+        );
+        let mut stmt = tx.prepare(&sql).map_err(db_err)?;
+        for (mut row, action) in rows {
+            if let Action::Merge = action {
+                entry.merged += 1;
+                continue;
+            }
+            let remapped = |row: &mut Vec<Value>, index: usize, parent: &str| {
+                if let Value::Text(id) = &row[index] {
+                    if let Some(new) = ids.get(parent).and_then(|map| map.get(id)) {
+                        row[index] = Value::Text(new.clone());
+                    }
+                }
+            };
+            if let Some(pk) = table.pk {
+                remapped(&mut row, pk, &table.name);
+            }
+            for (index, parent) in &table.fks {
+                remapped(&mut row, *index, parent);
+            }
+            let inserted = stmt
+                .execute(rusqlite::params_from_iter(&row))
+                .map_err(|e| format!("Failed to copy a row into {}: {e}", table.name))?;
+            if inserted == 0 {
+                // Link-table row that was already present
+                entry.merged += 1;
+                continue;
+            }
+            entry.copied += 1;
+            if let Action::Remap = action {
+                entry.remapped += 1;
+            }
+        }
+    }
+
+    tx.commit().map_err(db_err)?;
+    let _ = conn.execute_batch("DETACH DATABASE src");
+    Ok(report)
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How often `/api/workflows` is polled for finished workflows. Polling keeps
+/// notifications working whether or not anything subscribed to `/api/events`.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A workflow that reached a final status worth a notification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finished {
+    pub name: String,
+    pub status: String,
+}
+
+/// Last seen status of each workflow, to spot transitions between polls.
+#[derive(Default)]
+pub struct WorkflowWatch(Option<HashMap<String, String>>);
+
+impl WorkflowWatch {
+    /// Record `workflows` (the `data` of `/api/workflows`) and return those
+    /// that moved to completed or failed since the last call. The first call
+    /// only takes a baseline, so finished work isn't re-announced at launch.
+    pub fn update(&mut self, workflows: &[serde_json::Value]) -> Vec<Finished> {
+        let current: HashMap<String, (String, String)> = workflows
+            .iter()
+            .filter_map(|wf| {
+                let id = wf["id"].as_str()?.to_string();
+                let status = wf["status"].as_str()?.to_string();
+                let name = wf["name"].as_str().unwrap_or(&id).to_string();
+                Some((id, (name, status)))
+            })
+            .collect();
+
+        let finished = match &self.0 {
+            Some(previous) => current
+                .iter()
+                .filter(|(id, (_, status))| {
+                    matches!(status.as_str(), "completed" | "failed")
+                        && previous.get(*id).is_some_and(|before| before != status)
+                })
+                .map(|(_, (name, status))| Finished {
+                    name: name.clone(),
+                    status: status.clone(),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        self.0 = Some(current.into_iter().map(|(id, (_, status))| (id, status)).collect());
+        finished
+    }
+
+    /// Forget the baseline, e.g. while notifications are off.
+    pub fn reset(&mut self) {
+        self.0 = None;
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri_plugin_shell::process::CommandChild;
+
+/// How long a graceful stop waits for the sidecar before escalating to a hard kill.
+pub const GRACEFUL_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long to wait for the process to disappear after a hard kill.
+const KILL_WAIT: Duration = Duration::from_secs(1);
+
+/// Automatic restarts allowed within [`CRASH_RESTART_WINDOW`] before giving up.
+pub const CRASH_RESTART_LIMIT: usize = 5;
+pub const CRASH_RESTART_WINDOW: Duration = Duration::from_secs(60);
+/// Delay before the first automatic restart; doubles with each recent one.
+const CRASH_RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// When recent crash restarts happened, to back off and stop a crash loop.
+#[derive(Default)]
+pub struct CrashRestarts(Mutex<VecDeque<Instant>>);
+
+impl CrashRestarts {
+    /// Count another restart attempt and return how long to wait before it,
+    /// or `None` once the limit for the window is used up.
+    pub fn next_delay(&self) -> Option<Duration> {
+        let mut attempts = self.0.lock().ok()?;
+        let now = Instant::now();
+        while attempts
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > CRASH_RESTART_WINDOW)
+        {
+            attempts.pop_front();
+        }
+        if attempts.len() >= CRASH_RESTART_LIMIT {
+            return None;
+        }
+        let delay = CRASH_RESTART_DELAY * 2u32.pow(attempts.len() as u32);
+        attempts.push_back(now);
+        Some(delay)
+    }
+
+    /// Attempts counted in the current window.
+    pub fn count(&self) -> usize {
+        self.0.lock().map(|attempts| attempts.len()).unwrap_or_default()
+    }
+
+    /// Forget past attempts, e.g. after the user restarts the server by hand.
+    pub fn reset(&self) {
+        if let Ok(mut attempts) = self.0.lock() {
+            attempts.clear();
+        }
+    }
+}
+
+/// Where the sidecar's PID is recorded for external tooling: `caw.pid` next
+/// to the database it serves.
+pub fn pid_file(db_path: &str) -> Option<std::path::PathBuf> {
+    let dir = std::path::Path::new(db_path).parent()?;
+    Some(dir.join("caw.pid"))
+}
+
+/// Record `pid` for the sidecar serving `db_path`. Best-effort: a failure is
+/// logged and otherwise ignored.
+pub fn write_pid_file(db_path: &str, pid: u32) {
+    let Some(path) = pid_file(db_path) else {
+        return;
+    };
+    if let Err(e) = std::fs::write(&path, format!("{pid}\n")) {
+        crate::logs::desktop_log!("Warning: could not write {}: {e}", path.display());
+    }
+}
+
+/// The PID recorded for the sidecar serving `db_path`, if any.
+pub fn read_pid_file(db_path: &str) -> Option<u32> {
+    let text = std::fs::read_to_string(pid_file(db_path)?).ok()?;
+    text.trim().parse().ok()
+}
+
+pub fn remove_pid_file(db_path: &str) {
+    if let Some(path) = pid_file(db_path) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Set while a restart runs, so a second one backs off instead of racing it
+/// and orphaning a child.
+#[derive(Default)]
+pub struct RestartFlag(AtomicBool);
+
+impl RestartFlag {
+    /// Mark a restart as running, or `None` if one already is. The flag is
+    /// cleared when the returned claim drops, on every exit path.
+    pub fn claim(&self) -> Option<RestartClaim<'_>> {
+        self.0
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| RestartClaim(&self.0))
+    }
+}
+
+pub struct RestartClaim<'a>(&'a AtomicBool);
+
+impl Drop for RestartClaim<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// The step of a restart that failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestartStage {
+    /// The arguments were rejected before anything was stopped.
+    Config,
+    /// Another restart was already running; nothing was done.
+    Busy,
+    /// The binary couldn't be launched.
+    Spawn,
+    /// It launched but never answered `/health`.
+    Health,
+}
+
+/// Why `restart_server` failed, in a shape the UI can act on.
+#[derive(Clone, Debug, Serialize)]
+pub struct RestartFailure {
+    /// Always false; mirrors the `success` field of a successful restart.
+    pub success: bool,
+    pub stage: RestartStage,
+    pub message: String,
+    pub port: u16,
+}
+
+impl RestartFailure {
+    pub fn new(stage: RestartStage, message: impl Into<String>, port: u16) -> Self {
+        Self {
+            success: false,
+            stage,
+            message: message.into(),
+            port,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StopMethod {
+    /// The process exited on its own after SIGTERM.
+    Graceful,
+    /// The process was hard-killed (immediately, or after SIGTERM timed out).
+    Forced,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct StopOutcome {
+    pub method: StopMethod,
+    /// Whether the process was confirmed gone afterwards.
+    pub exited: bool,
+}
+
+/// The sidecar the app manages.
+pub enum Sidecar {
+    /// Spawned by this session.
+    Child(CommandChild),
+    /// Left running by a session that crashed and reused at launch. There is
+    /// no child handle, only the PID that session recorded.
+    Adopted(u32),
+}
+
+impl Sidecar {
+    pub fn pid(&self) -> u32 {
+        match self {
+            Self::Child(child) => child.pid(),
+            Self::Adopted(pid) => *pid,
+        }
+    }
+
+    /// Hard-kill the process. Returns false if that failed.
+    fn kill(self) -> bool {
+        match self {
+            Self::Child(child) => child.kill().is_ok(),
+            Self::Adopted(pid) => crate::procs::kill(pid).is_ok(),
+        }
+    }
+}
+
+/// Send SIGTERM. Returns false if the signal could not be delivered.
+#[cfg(unix)]
+fn terminate(pid: u32) -> bool {
+    unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) == 0 }
+}
+
+#[cfg(not(unix))]
+fn terminate(_pid: u32) -> bool {
+    false
+}
+
+/// Whether a process with this PID still exists.
+#[cfg(unix)]
+pub fn is_alive(pid: u32) -> bool {
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+pub fn is_alive(pid: u32) -> bool {
+    let pid = sysinfo::Pid::from_u32(pid);
+    let mut system = sysinfo::System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some()
+}
+
+async fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while is_alive(pid) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    true
+}
+
+/// Hard-kill the sidecar immediately.
+pub async fn force_kill(sidecar: Sidecar) -> StopOutcome {
+    let pid = sidecar.pid();
+    let killed = sidecar.kill();
+    let exited = if cfg!(unix) {
+        wait_for_exit(pid, KILL_WAIT).await
+    } else {
+        killed
+    };
+    StopOutcome {
+        method: StopMethod::Forced,
+        exited,
+    }
+}
+
+/// How long the app's exit handler waits for a graceful stop. The event loop
+/// is blocked meanwhile, so this is shorter than [`GRACEFUL_STOP_TIMEOUT`].
+pub const EXIT_STOP_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// [`stop_gracefully`] for synchronous contexts such as the exit handler,
+/// polling on the current thread instead of awaiting.
+pub fn stop_gracefully_blocking(sidecar: Sidecar, timeout: Duration) -> StopMethod {
+    let pid = sidecar.pid();
+    if terminate(pid) {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if !is_alive(pid) {
+                return StopMethod::Graceful;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+    sidecar.kill();
+    StopMethod::Forced
+}
+
+/// SIGTERM the sidecar and wait up to `timeout` for it to exit, escalating to
+/// a hard kill if it doesn't. On platforms without SIGTERM this is a hard kill.
+pub async fn stop_gracefully(sidecar: Sidecar, timeout: Duration) -> StopOutcome {
+    let pid = sidecar.pid();
+    if terminate(pid) && wait_for_exit(pid, timeout).await {
+        return StopOutcome {
+            method: StopMethod::Graceful,
+            exited: true,
+        };
+    }
+    force_kill(sidecar).await
+}
@@ -0,0 +1,168 @@
+use std::ffi::OsString;
+use std::path::Path;
+
+use serde::Serialize;
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+#[derive(Debug, Serialize)]
+pub struct CawProcess {
+    pub pid: u32,
+    pub port: Option<String>,
+    pub db: Option<String>,
+    pub uptime_secs: u64,
+    pub command: String,
+}
+
+fn arg_value(cmd: &[OsString], flag: &str) -> Option<String> {
+    cmd.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| cmd.get(i + 1))
+        .map(|value| value.to_string_lossy().into_owned())
+}
+
+/// Whether a command line is a caw server: an executable named `caw` (or the
+/// target-suffixed `caw-<triple>` dev binary) started with `--server`.
+fn is_caw_server(cmd: &[OsString]) -> bool {
+    let Some(program) = cmd.first() else {
+        return false;
+    };
+    let stem = Path::new(program)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let named_caw = stem == "caw"
+        || stem
+            .strip_prefix("caw-")
+            .is_some_and(|triple| triple.contains('-') && !triple.starts_with("desktop"));
+    named_caw && cmd.iter().any(|arg| arg == "--server")
+}
+
+fn describe(pid: Pid, process: &Process) -> CawProcess {
+    let cmd = process.cmd();
+    CawProcess {
+        pid: pid.as_u32(),
+        port: arg_value(cmd, "--port"),
+        db: arg_value(cmd, "--db"),
+        uptime_secs: process.run_time(),
+        command: cmd
+            .iter()
+            .map(|arg| arg.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn refreshed_system(processes: ProcessesToUpdate<'_>) -> System {
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        processes,
+        true,
+        ProcessRefreshKind::nothing().with_cmd(UpdateKind::Always),
+    );
+    system
+}
+
+/// Every caw server process on the machine, excluding this app itself.
+pub fn list() -> Vec<CawProcess> {
+    let own = std::process::id();
+    let system = refreshed_system(ProcessesToUpdate::All);
+    let mut procs: Vec<_> = system
+        .processes()
+        .iter()
+        .filter(|(pid, process)| pid.as_u32() != own && is_caw_server(process.cmd()))
+        .map(|(pid, process)| describe(*pid, process))
+        .collect();
+    procs.sort_by_key(|p| p.pid);
+    procs
+}
+
+/// The caw server with `pid`, looking at that process only. `None` if it has
+/// exited or the PID now belongs to something else.
+pub fn caw_server(pid: u32) -> Option<CawProcess> {
+    let pid = Pid::from_u32(pid);
+    let system = refreshed_system(ProcessesToUpdate::Some(&[pid]));
+    let process = system.process(pid)?;
+    is_caw_server(process.cmd()).then(|| describe(pid, process))
+}
+
+/// Memory and CPU use of one process.
+#[derive(Debug, Serialize)]
+pub struct ResourceUsage {
+    pub pid: u32,
+    /// Resident set size.
+    pub rss_bytes: u64,
+    pub virtual_bytes: u64,
+    /// Share of one core, so a busy multithreaded process can exceed 100.
+    pub cpu_percent: f32,
+}
+
+/// Sample `pid`'s resource use. CPU needs two readings, so this blocks for
+/// sysinfo's minimum update interval. `None` if the process is gone.
+pub fn usage(pid: u32) -> Option<ResourceUsage> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    let kind = ProcessRefreshKind::nothing().with_memory().with_cpu();
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, kind);
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, kind);
+    let process = system.process(pid)?;
+    Some(ResourceUsage {
+        pid: pid.as_u32(),
+        rss_bytes: process.memory(),
+        virtual_bytes: process.virtual_memory(),
+        cpu_percent: process.cpu_usage(),
+    })
+}
+
+/// Kill `pid`, but only if it is a caw server and not this app.
+pub fn kill(pid: u32) -> Result<(), String> {
+    if pid == std::process::id() {
+        return Err("Refusing to kill the desktop app itself".to_string());
+    }
+    let target = Pid::from_u32(pid);
+    let system = refreshed_system(ProcessesToUpdate::Some(&[target]));
+    let process = system
+        .process(target)
+        .ok_or_else(|| format!("No process with pid {pid}"))?;
+    if !is_caw_server(process.cmd()) {
+        return Err(format!("Process {pid} does not look like a caw server"));
+    }
+    if process.kill() {
+        Ok(())
+    } else {
+        Err(format!("Failed to kill process {pid}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn recognizes_caw_servers() {
+        assert!(is_caw_server(&cmd(&["/Applications/Caw.app/Contents/MacOS/caw", "--server"])));
+        assert!(is_caw_server(&cmd(&["caw-aarch64-apple-darwin", "--server", "--port", "3100"])));
+        assert!(is_caw_server(&cmd(&["/usr/local/bin/caw.exe", "--server"])));
+    }
+
+    #[test]
+    fn ignores_other_caw_commands_and_programs() {
+        assert!(!is_caw_server(&cmd(&[])));
+        assert!(!is_caw_server(&cmd(&["caw", "list"])));
+        assert!(!is_caw_server(&cmd(&["caw-desktop", "--server"])));
+        assert!(!is_caw_server(&cmd(&["caw-cli", "--server"])));
+        assert!(!is_caw_server(&cmd(&["node", "caw", "--server"])));
+    }
+
+    #[test]
+    fn reads_flag_values() {
+        let cmd = cmd(&["caw", "--server", "--port", "3100", "--db"]);
+        assert_eq!(arg_value(&cmd, "--port").as_deref(), Some("3100"));
+        assert_eq!(arg_value(&cmd, "--db"), None);
+        assert_eq!(arg_value(&cmd, "--missing"), None);
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::logs::desktop_log;
+
+/// Per-repo sidecar preferences from `.caw/config.toml`, next to the project
+/// database, so they travel with the repo.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub port: Option<u16>,
+    pub transport: Option<String>,
+    /// Appended to the sidecar's command line after the app's own arguments.
+    pub extra_args: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LoadedProject {
+    pub path: PathBuf,
+    pub config: ProjectConfig,
+}
+
+/// Where an effective setting came from, highest precedence first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Env,
+    /// Desktop settings (`desktop.json`), i.e. an explicit choice in the app.
+    Settings,
+    Project,
+    Default,
+    /// Changed at runtime and not persisted.
+    Session,
+}
+
+pub fn config_path(repo_root: &str) -> PathBuf {
+    Path::new(repo_root).join(".caw").join("config.toml")
+}
+
+/// Load the project config for `repo_root`. A missing file is normal; an
+/// unreadable or invalid one is ignored with a warning.
+pub fn load(repo_root: &str) -> Option<LoadedProject> {
+    let path = config_path(repo_root);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            desktop_log!("Warning: ignoring {}: {e}", path.display());
+            return None;
+        }
+    };
+    match toml::from_str(&text) {
+        Ok(config) => Some(LoadedProject { path, config }),
+        Err(e) => {
+            desktop_log!("Warning: ignoring invalid {}: {e}", path.display());
+            None
+        }
+    }
+}
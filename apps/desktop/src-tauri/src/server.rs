@@ -0,0 +1,144 @@
+use std::net::TcpListener;
+
+use serde::Serialize;
+
+pub const DEFAULT_PORT: u16 = 3100;
+
+/// How the app talks to the sidecar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    /// REST + WebSocket + MCP over localhost TCP.
+    #[default]
+    Http,
+}
+
+impl Transport {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "http" => Ok(Self::Http),
+            // The app's UI is built on the sidecar's REST API, which stdio doesn't carry
+            "stdio" => Err("The stdio transport only serves MCP; the app needs HTTP".to_string()),
+            other => Err(format!("Unknown transport: {other}")),
+        }
+    }
+
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            Self::Http => "http",
+        }
+    }
+}
+
+/// Where the sidecar listens, shared by every client URL in the app.
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub port: u16,
+    pub transport: Transport,
+    /// Extra sidecar arguments from the project config.
+    pub extra_args: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            transport: Transport::default(),
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn base_url(&self) -> String {
+        format!("http://localhost:{}", self.port)
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url())
+    }
+
+    pub fn health_url(&self) -> String {
+        self.url("/health")
+    }
+
+    /// Arguments the app passes to the sidecar binary.
+    pub fn sidecar_args(&self, db_path: &str) -> Vec<String> {
+        [
+            "--server",
+            "--transport",
+            self.transport.as_arg(),
+            "--port",
+            &self.port.to_string(),
+            "--db",
+            db_path,
+        ]
+        .map(String::from)
+        .into_iter()
+        .chain(self.extra_args.iter().cloned())
+        .collect()
+    }
+}
+
+/// Whether nothing is currently bound to `port` on loopback.
+pub fn is_port_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// Ports above the configured one tried at launch when it is taken.
+const PORT_PROBE_RANGE: u16 = 20;
+
+/// `port` if it is free, otherwise the next free port above it.
+pub fn find_free_port(port: u16) -> Option<u16> {
+    (port..=port.saturating_add(PORT_PROBE_RANGE)).find(|&candidate| is_port_free(candidate))
+}
+
+/// Port from `CAW_PORT`, if set. An unparsable value is ignored with a warning.
+pub fn env_port() -> Option<u16> {
+    let value = std::env::var("CAW_PORT").ok().filter(|v| !v.trim().is_empty())?;
+    match value.trim().parse() {
+        Ok(port) => Some(port),
+        Err(_) => {
+            crate::logs::desktop_log!("Warning: ignoring invalid CAW_PORT={value:?}");
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_elevated() -> bool {
+    unsafe { libc::geteuid() == 0 }
+}
+
+// Windows has no privileged ports, but check the range there too
+#[cfg(not(unix))]
+fn is_elevated() -> bool {
+    false
+}
+
+/// Reject port 0 (the OS would pick one) and ports that need elevated
+/// privileges to bind.
+pub fn check_port_range(port: u16) -> Result<(), String> {
+    if port == 0 {
+        return Err("Port 0 is not a fixed port; choose 1024 or above".to_string());
+    }
+    if port < 1024 && !is_elevated() {
+        return Err(format!("Port {port} is privileged; choose 1024 or above"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_range() {
+        assert!(check_port_range(0).is_err());
+        assert!(check_port_range(1024).is_ok());
+        assert!(check_port_range(DEFAULT_PORT).is_ok());
+        assert!(check_port_range(u16::MAX).is_ok());
+        assert_eq!(check_port_range(80).is_ok(), is_elevated());
+        assert_eq!(check_port_range(1023).is_ok(), is_elevated());
+    }
+}
@@ -0,0 +1,214 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+impl Theme {
+    pub fn parse(theme: &str) -> Result<Self, String> {
+        match theme {
+            "light" => Ok(Self::Light),
+            "dark" => Ok(Self::Dark),
+            "system" => Ok(Self::System),
+            other => Err(format!("Unknown theme: {other}")),
+        }
+    }
+}
+
+/// Whether the app shows a Dock icon (macOS only).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivationPolicy {
+    #[default]
+    Regular,
+    /// No Dock icon; the app is driven from its windows and tray.
+    Accessory,
+}
+
+impl ActivationPolicy {
+    pub fn parse(policy: &str) -> Result<Self, String> {
+        match policy {
+            "regular" => Ok(Self::Regular),
+            "accessory" => Ok(Self::Accessory),
+            other => Err(format!("Unknown activation policy: {other}")),
+        }
+    }
+}
+
+/// How much window chrome to show.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChromeMode {
+    #[default]
+    Normal,
+    /// No menu bar where the platform allows, and tighter traffic lights on macOS.
+    Minimal,
+}
+
+impl ChromeMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "normal" => Ok(Self::Normal),
+            "minimal" => Ok(Self::Minimal),
+            other => Err(format!("Unknown chrome mode: {other}")),
+        }
+    }
+}
+
+/// Inset of the macOS traffic lights from the window's top-left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TrafficLights {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Default for TrafficLights {
+    fn default() -> Self {
+        Self { x: 14.0, y: 18.0 }
+    }
+}
+
+/// Desktop-only preferences persisted to `~/.caw/desktop.json`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DesktopSettings {
+    pub theme: Theme,
+    /// Database pinned by the user; overrides git/global resolution at launch.
+    pub db_path: Option<String>,
+    /// Database the sidecar last ran on. Outside a git repo it is reopened
+    /// instead of the global database, so launching from `~` doesn't lose it.
+    pub last_db_path: Option<String>,
+    /// Databases the sidecar ran on, most recent first, for File ▸ Recent Databases.
+    pub recent_dbs: Vec<String>,
+    /// Re-spawn the sidecar when it exits unexpectedly.
+    pub auto_restart_on_crash: bool,
+    /// Port chosen via `set_port`; `None` uses the default.
+    pub port: Option<u16>,
+    /// Stop the sidecar after this many idle minutes; 0 keeps it running.
+    pub idle_stop_minutes: u32,
+    /// Inbox shows only unread messages (the TUI's `/unread`).
+    pub unread_only: bool,
+    /// Prime the sidecar's SQLite cache with a cheap query once it is healthy.
+    pub prewarm_db: bool,
+    /// Send sidecar requests through the system proxy instead of directly.
+    pub use_system_proxy: bool,
+    pub activation_policy: ActivationPolicy,
+    /// Keep app windows above other applications.
+    pub always_on_top: bool,
+    pub chrome_mode: ChromeMode,
+    /// Traffic light inset in normal chrome mode (macOS).
+    pub traffic_lights: TrafficLights,
+    /// Consecutive `/health` timeouts from a live sidecar that count as hung
+    /// and force a restart; 0 disables the watchdog.
+    pub hang_timeouts: u32,
+    /// The timeouts must all fall within this many seconds.
+    pub hang_window_secs: u64,
+    /// Opt out of macOS App Nap while a sidecar runs so crashes and health
+    /// changes are noticed promptly. Turn off to save battery.
+    pub prevent_app_nap: bool,
+    /// How long a sidecar restart waits for in-flight requests to finish.
+    pub request_drain_timeout_secs: u64,
+    /// How long a sidecar start or restart may take to become healthy (5–120).
+    pub startup_timeout_secs: u64,
+    /// At launch, adopt a caw server left running on the port by a crashed
+    /// session (if it serves the same database) instead of killing it.
+    pub reuse_existing_server: bool,
+    /// Timeout for `/health` checks behind status queries; kept short so a
+    /// polling UI doesn't stall while the sidecar is down. Startup waits
+    /// use their own, longer timeout.
+    pub status_timeout_ms: u64,
+    /// Notify when a workflow completes or fails while the app is in the background.
+    pub notifications_enabled: bool,
+}
+
+impl Default for DesktopSettings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            db_path: None,
+            last_db_path: None,
+            recent_dbs: Vec::new(),
+            auto_restart_on_crash: true,
+            port: None,
+            idle_stop_minutes: 0,
+            unread_only: false,
+            prewarm_db: true,
+            use_system_proxy: false,
+            activation_policy: ActivationPolicy::default(),
+            always_on_top: false,
+            chrome_mode: ChromeMode::default(),
+            traffic_lights: TrafficLights::default(),
+            hang_timeouts: 3,
+            hang_window_secs: 120,
+            prevent_app_nap: true,
+            request_drain_timeout_secs: 5,
+            startup_timeout_secs: 30,
+            reuse_existing_server: false,
+            status_timeout_ms: 750,
+            notifications_enabled: true,
+        }
+    }
+}
+
+pub fn settings_path() -> PathBuf {
+    crate::db::caw_home().join("desktop.json")
+}
+
+/// Load settings, falling back to defaults if the file is missing or unreadable.
+pub fn load() -> DesktopSettings {
+    let path = settings_path();
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(_) => return DesktopSettings::default(),
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        crate::logs::desktop_log!("Warning: ignoring invalid {}: {e}", path.display());
+        DesktopSettings::default()
+    })
+}
+
+/// Set in safe mode, where settings start from defaults: saving them would
+/// replace the user's real file.
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Make [`save`] a no-op for the rest of the session.
+pub fn set_read_only() {
+    READ_ONLY.store(true, Ordering::SeqCst);
+}
+
+pub fn save(settings: &DesktopSettings) -> Result<(), String> {
+    if READ_ONLY.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    let path = settings_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Move `desktop.json` aside to `desktop.json.bak-<unix secs>` so defaults load
+/// next time. Returns the backup path, or `None` if there was no file.
+pub fn back_up_and_remove() -> Result<Option<PathBuf>, String> {
+    let path = settings_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let backup = path.with_file_name(format!("desktop.json.bak-{stamp}"));
+    std::fs::rename(&path, &backup)
+        .map_err(|e| format!("Failed to back up {}: {e}", path.display()))?;
+    Ok(Some(backup))
+}
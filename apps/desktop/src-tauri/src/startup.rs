@@ -0,0 +1,65 @@
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Milliseconds spent in each startup phase, for `startup_metrics`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StartupTimings {
+    pub config_ms: u64,
+    pub git_ms: u64,
+    pub spawn_ms: u64,
+    pub appearance_ms: u64,
+    pub window_ms: u64,
+    /// From the start of setup until `/health` first succeeded.
+    pub first_health_ms: Option<u64>,
+    /// Duration of the post-health warm-up query, once it has completed.
+    pub warm_ms: Option<u64>,
+    pub warmed: bool,
+}
+
+impl StartupTimings {
+    pub fn summary(&self) -> String {
+        let first_health = self
+            .first_health_ms
+            .map_or_else(|| "n/a".to_string(), |ms| format!("{ms}ms"));
+        format!(
+            "startup: config={}ms git={}ms spawn={}ms appearance={}ms window={}ms \
+             first-health={first_health}",
+            self.config_ms, self.git_ms, self.spawn_ms, self.appearance_ms, self.window_ms
+        )
+    }
+}
+
+pub struct StartupMetrics {
+    pub started: Instant,
+    pub timings: Mutex<StartupTimings>,
+}
+
+impl StartupMetrics {
+    pub fn new(started: Instant, timings: StartupTimings) -> Self {
+        Self {
+            started,
+            timings: Mutex::new(timings),
+        }
+    }
+
+    pub fn snapshot(&self) -> StartupTimings {
+        match self.timings.lock() {
+            Ok(timings) => timings.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+}
+
+/// Run `f` inside a `startup` span for `phase`, returning its result and how
+/// long it took in milliseconds. The span records the same `ms`, so a
+/// subscriber sees each phase without parsing the summary line.
+pub fn timed<T>(phase: &'static str, f: impl FnOnce() -> T) -> (T, u64) {
+    let span = tracing::info_span!("startup", phase, ms = tracing::field::Empty);
+    let start = Instant::now();
+    let value = span.in_scope(f);
+    let ms = start.elapsed().as_millis() as u64;
+    span.record("ms", ms);
+    (value, ms)
+}
@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// How often the database size is sampled.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// One day of history at the default sampling interval.
+const MAX_SAMPLES: usize = 288;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SizeSample {
+    /// Unix timestamp (seconds).
+    pub timestamp: u64,
+    pub db_bytes: u64,
+    pub wal_bytes: u64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct StatsFile {
+    #[serde(default)]
+    db_size_samples: VecDeque<SizeSample>,
+}
+
+/// Ring buffer of database size samples, persisted to `~/.caw/desktop-stats.json`.
+pub struct SizeHistory(Mutex<VecDeque<SizeSample>>);
+
+fn stats_path() -> PathBuf {
+    crate::db::caw_home().join("desktop-stats.json")
+}
+
+fn file_size(path: &str) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Current size of the database file and its `-wal` companion.
+pub fn sample(db_path: &str) -> SizeSample {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    SizeSample {
+        timestamp,
+        db_bytes: file_size(db_path),
+        wal_bytes: file_size(&format!("{db_path}-wal")),
+    }
+}
+
+impl SizeHistory {
+    pub fn load() -> Self {
+        let samples = std::fs::read_to_string(stats_path())
+            .ok()
+            .and_then(|text| serde_json::from_str::<StatsFile>(&text).ok())
+            .map(|file| file.db_size_samples)
+            .unwrap_or_default();
+        Self(Mutex::new(samples))
+    }
+
+    pub fn samples(&self) -> Vec<SizeSample> {
+        self.0
+            .lock()
+            .map(|samples| samples.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Append a sample, dropping the oldest beyond the cap, and persist.
+    pub fn record(&self, sample: SizeSample) {
+        let file = {
+            let Ok(mut samples) = self.0.lock() else {
+                return;
+            };
+            samples.push_back(sample);
+            while samples.len() > MAX_SAMPLES {
+                samples.pop_front();
+            }
+            StatsFile {
+                db_size_samples: samples.clone(),
+            }
+        };
+
+        let path = stats_path();
+        if let Ok(text) = serde_json::to_string(&file) {
+            if let Err(e) = std::fs::write(&path, text) {
+                crate::logs::desktop_log!("Warning: failed to write {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiskSpace {
+    /// Mount point of the filesystem holding the database.
+    pub mount_point: String,
+    pub free_bytes: u64,
+    pub total_bytes: u64,
+    pub db_bytes: u64,
+    pub wal_bytes: u64,
+}
+
+/// Free and total space on the filesystem that holds `db_path`, plus the
+/// database's current size. A full disk otherwise surfaces as SQLite's
+/// "disk I/O error", which looks like corruption.
+pub fn disk_space(db_path: &str) -> Result<DiskSpace, String> {
+    // The database may not exist yet; measure the closest existing ancestor
+    let dir = Path::new(db_path)
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .unwrap_or(Path::new("."));
+    let dir = std::fs::canonicalize(dir)
+        .map_err(|e| format!("Cannot resolve {}: {e}", dir.display()))?;
+
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|disk| dir.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .ok_or_else(|| format!("No filesystem found for {}", dir.display()))?;
+    let size = sample(db_path);
+    Ok(DiskSpace {
+        mount_point: disk.mount_point().to_string_lossy().into_owned(),
+        free_bytes: disk.available_space(),
+        total_bytes: disk.total_space(),
+        db_bytes: size.db_bytes,
+        wal_bytes: size.wal_bytes,
+    })
+}
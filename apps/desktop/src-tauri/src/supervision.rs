@@ -0,0 +1,46 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Longest a pause may last; supervision resumes on its own after this.
+pub const MAX_PAUSE: Duration = Duration::from_secs(10 * 60);
+
+/// Whether the health monitor, hang watchdog, and crash restarts are standing
+/// down, e.g. while the user runs a migration or backup from the CLI.
+#[derive(Default)]
+pub struct Supervision(Mutex<Option<Instant>>);
+
+impl Supervision {
+    /// Pause until `duration` from now, capped at [`MAX_PAUSE`]. Returns how
+    /// long the pause will actually last.
+    pub fn pause(&self, duration: Duration) -> Duration {
+        let duration = duration.min(MAX_PAUSE);
+        if let Ok(mut until) = self.0.lock() {
+            *until = Some(Instant::now() + duration);
+        }
+        duration
+    }
+
+    /// Resume now. Returns false if supervision wasn't paused.
+    pub fn resume(&self) -> bool {
+        let was_paused = self.is_paused();
+        if let Ok(mut until) = self.0.lock() {
+            *until = None;
+        }
+        was_paused
+    }
+
+    /// Time left before supervision resumes; `None` when not paused.
+    pub fn remaining(&self) -> Option<Duration> {
+        let mut until = self.0.lock().ok()?;
+        let left = until.map(|until| until.saturating_duration_since(Instant::now()))?;
+        if left.is_zero() {
+            *until = None;
+            return None;
+        }
+        Some(left)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.remaining().is_some()
+    }
+}
@@ -0,0 +1,152 @@
+use tauri_plugin_shell::ShellExt;
+
+/// Quote `arg` for a POSIX shell.
+fn sh_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+fn quote(arg: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{arg}\"")
+    } else {
+        sh_quote(arg)
+    }
+}
+
+/// `args` with the values of credential-looking flags (`--token`, `--api-key`,
+/// …) replaced by `<redacted>`.
+pub fn redact_args(args: &[String]) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut redact_next = false;
+    for arg in args {
+        if redact_next {
+            redacted.push("<redacted>".to_string());
+            redact_next = false;
+            continue;
+        }
+        match arg.split_once('=') {
+            Some((flag, _)) if flag.starts_with("--") && crate::environment::looks_secret(flag) => {
+                redacted.push(format!("{flag}=<redacted>"));
+            }
+            _ => {
+                redact_next = arg.starts_with("--") && crate::environment::looks_secret(arg);
+                redacted.push(arg.clone());
+            }
+        }
+    }
+    redacted
+}
+
+/// The shell command line that runs `sidecar` with `args`, as the app does,
+/// with credentials redacted.
+pub fn sidecar_command_line(sidecar: &str, args: &[String]) -> String {
+    let mut parts = vec![quote(sidecar)];
+    for arg in redact_args(args) {
+        if arg.starts_with("--") && !arg.contains('=') {
+            parts.push(arg);
+        } else {
+            parts.push(quote(&arg));
+        }
+    }
+    parts.join(" ")
+}
+
+/// Open the platform terminal with `command_line`. When `execute` is false the
+/// command is only printed in the new window, ready to copy and run.
+pub fn open<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    command_line: &str,
+    execute: bool,
+) -> Result<(), String> {
+    let shell = app.shell();
+    let spawn = |program: &str, args: Vec<String>| {
+        shell
+            .command(program)
+            .args(args)
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to open {program}: {e}"))
+    };
+
+    if cfg!(target_os = "macos") {
+        let script = if execute {
+            command_line.to_string()
+        } else {
+            format!("echo {}", sh_quote(command_line))
+        };
+        let script = script.replace('\\', "\\\\").replace('"', "\\\"");
+        spawn(
+            "osascript",
+            vec![
+                "-e".into(),
+                format!("tell application \"Terminal\" to do script \"{script}\""),
+                "-e".into(),
+                "tell application \"Terminal\" to activate".into(),
+            ],
+        )
+    } else if cfg!(windows) {
+        let run = if execute {
+            command_line.to_string()
+        } else {
+            format!("echo {command_line}")
+        };
+        spawn("wt", vec!["cmd".into(), "/k".into(), run])
+    } else {
+        let run = if execute {
+            format!("{command_line}; exec \"${{SHELL:-sh}}\"")
+        } else {
+            format!("echo {}; exec \"${{SHELL:-sh}}\"", sh_quote(command_line))
+        };
+        let args = vec!["-e".into(), "sh".into(), "-c".into(), run];
+        spawn("x-terminal-emulator", args.clone()).or_else(|_| spawn("xterm", args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sh_quote_wraps_in_single_quotes() {
+        assert_eq!(sh_quote("plain"), "'plain'");
+        assert_eq!(sh_quote("$HOME; rm -rf *"), "'$HOME; rm -rf *'");
+        assert_eq!(sh_quote(""), "''");
+    }
+
+    #[test]
+    fn sh_quote_escapes_embedded_single_quotes() {
+        assert_eq!(sh_quote("it's"), r"'it'\''s'");
+    }
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn redacts_the_value_after_a_secret_flag() {
+        let args = strings(&["--api-key", "sk-123", "--port", "3100"]);
+        assert_eq!(redact_args(&args), strings(&["--api-key", "<redacted>", "--port", "3100"]));
+    }
+
+    #[test]
+    fn redacts_inline_secret_values() {
+        let args = strings(&["--auth-token=abc", "--db=/tmp/x.db"]);
+        assert_eq!(redact_args(&args), strings(&["--auth-token=<redacted>", "--db=/tmp/x.db"]));
+    }
+
+    #[test]
+    fn leaves_positional_arguments_that_mention_secrets_alone() {
+        let args = strings(&["serve", "token", "--verbose"]);
+        assert_eq!(redact_args(&args), args);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_line_quotes_values_but_not_flags() {
+        let args = ["--port".to_string(), "3100".to_string(), "--db=/a b.db".to_string()];
+        assert_eq!(
+            sidecar_command_line("/Apps/caw app/caw", &args),
+            "'/Apps/caw app/caw' --port '3100' '--db=/a b.db'"
+        );
+    }
+}
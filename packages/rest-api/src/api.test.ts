@@ -579,6 +579,25 @@ describe('config routes', () => {
   });
 });
 
+// --- Capabilities ---
+
+describe('capabilities routes', () => {
+  test('GET /api/capabilities lists supported features', async () => {
+    const res = await req('GET', '/api/capabilities');
+    expect(res.status).toBe(200);
+    const body = (await res.json()) as { data: { api_version: string; features: string[] } };
+    expect(body.data.api_version).toBe('1');
+    expect(body.data.features).toContain('checkpoint');
+  });
+
+  test('GET /api/capabilities reports the server version', async () => {
+    const versioned = createRestApi(db, undefined, { version: '1.2.3' });
+    const res = await apiRequest(versioned.handle, 'GET', '/api/capabilities');
+    const body = (await res.json()) as { data: { version: string | null } };
+    expect(body.data.version).toBe('1.2.3');
+  });
+});
+
 // --- 404 ---
 
 describe('routing', () => {
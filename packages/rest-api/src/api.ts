@@ -2,9 +2,12 @@ import type { DatabaseType } from '@caw/core';
 import { applyCors, handlePreflight } from './middleware';
 import type { Router } from './router';
 import { createRouter } from './router';
+import { registerAdminRoutes } from './routes/admin';
 import { registerAgentRoutes } from './routes/agents';
+import { registerCapabilitiesRoutes } from './routes/capabilities';
 import { registerCheckpointRoutes } from './routes/checkpoints';
 import { registerConfigRoutes } from './routes/config';
+import { registerEventRoutes } from './routes/events';
 import { registerExecutionRoutes, type SpawnerProvider } from './routes/execution';
 import { registerLockRoutes } from './routes/locks';
 import { registerMessageRoutes } from './routes/messages';
@@ -22,6 +25,8 @@ import type { Broadcaster } from './ws/broadcaster';
 export interface RestApiOptions {
   spawner?: SpawnerProvider;
   repoPath?: string;
+  /** Server version reported by `/api/capabilities`. */
+  version?: string;
 }
 
 export interface RestApi {
@@ -52,6 +57,9 @@ export function createRestApi(
   registerRepositoryRoutes(router, db);
   registerSessionRoutes(router, db);
   registerExecutionRoutes(router, db, broadcaster, options?.spawner);
+  registerAdminRoutes(router, db);
+  registerCapabilitiesRoutes(router, options?.version);
+  registerEventRoutes(router, broadcaster);
 
   async function handle(req: Request): Promise<Response> {
     // Handle CORS preflight
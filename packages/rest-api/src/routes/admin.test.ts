@@ -0,0 +1,50 @@
+import { afterEach, beforeEach, describe, expect, it } from 'bun:test';
+import type { DatabaseType } from '@caw/core';
+import { createRestApi } from '../api';
+import { apiRequest, createTestDb } from '../test-utils';
+
+describe('Admin Routes', () => {
+  let db: DatabaseType;
+
+  beforeEach(() => {
+    db = createTestDb();
+  });
+
+  afterEach(() => {
+    db.close();
+  });
+
+  describe('POST /api/admin/checkpoint', () => {
+    it('checkpoints the database', async () => {
+      const api = createRestApi(db);
+      const res = await apiRequest(api.handle, 'POST', '/api/admin/checkpoint');
+
+      expect(res.status).toBe(200);
+      const body = (await res.json()) as { data: { busy: number } };
+      expect(body.data.busy).toBe(0);
+    });
+  });
+
+  describe('POST /api/admin/migrate', () => {
+    it('reports nothing applied on a current schema', async () => {
+      const api = createRestApi(db);
+      const res = await apiRequest(api.handle, 'POST', '/api/admin/migrate');
+
+      expect(res.status).toBe(200);
+      const body = (await res.json()) as { data: { applied: number[]; versions: number[] } };
+      expect(body.data.applied).toEqual([]);
+      expect(body.data.versions.length).toBeGreaterThan(0);
+    });
+  });
+
+  describe('POST /api/admin/integrity-check', () => {
+    it('reports ok for a sound database', async () => {
+      const api = createRestApi(db);
+      const res = await apiRequest(api.handle, 'POST', '/api/admin/integrity-check');
+
+      expect(res.status).toBe(200);
+      const body = (await res.json()) as { data: { result: string[] } };
+      expect(body.data.result).toEqual(['ok']);
+    });
+  });
+});
@@ -0,0 +1,46 @@
+import type { DatabaseType } from '@caw/core';
+import { getAppliedVersions, runMigrations } from '@caw/core';
+import { ok } from '../response';
+import type { Router } from '../router';
+
+export interface CheckpointResult {
+  busy: number;
+  log: number;
+  checkpointed: number;
+}
+
+export interface MigrateResult {
+  /** Versions this call applied; empty when the schema was current. */
+  applied: number[];
+  versions: number[];
+}
+
+export interface IntegrityCheckResult {
+  /** `PRAGMA integrity_check` output: `['ok']` when the database is sound. */
+  result: string[];
+}
+
+export function registerAdminRoutes(router: Router, db: DatabaseType) {
+  // Fold the WAL into the main database file, e.g. before the desktop app
+  // stops the server or copies the database
+  router.post('/api/admin/checkpoint', () => {
+    const result = db.prepare('PRAGMA wal_checkpoint(TRUNCATE)').get() as CheckpointResult;
+    return ok(result);
+  });
+
+  // Apply pending schema migrations without restarting the server
+  router.post('/api/admin/migrate', () => {
+    const before = new Set(getAppliedVersions(db));
+    runMigrations(db);
+    const versions = getAppliedVersions(db);
+    return ok<MigrateResult>({
+      applied: versions.filter((version) => !before.has(version)),
+      versions,
+    });
+  });
+
+  router.post('/api/admin/integrity-check', () => {
+    const rows = db.prepare('PRAGMA integrity_check').all() as { integrity_check: string }[];
+    return ok<IntegrityCheckResult>({ result: rows.map((row) => row.integrity_check) });
+  });
+}
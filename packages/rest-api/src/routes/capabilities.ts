@@ -0,0 +1,25 @@
+import { ok } from '../response';
+import type { Router } from '../router';
+
+/** Bumped when an existing route changes shape, so clients can adapt. */
+export const API_VERSION = '1';
+
+/** Optional features clients such as the desktop app check before use. */
+export const FEATURES = ['checkpoint', 'migrate', 'integrity_check'];
+
+export interface CapabilitiesResponse {
+  api_version: string;
+  /** Version of the server build, when the host passed one in. */
+  version: string | null;
+  features: string[];
+}
+
+export function registerCapabilitiesRoutes(router: Router, version?: string) {
+  router.get('/api/capabilities', () =>
+    ok<CapabilitiesResponse>({
+      api_version: API_VERSION,
+      version: version ?? null,
+      features: FEATURES,
+    }),
+  );
+}
@@ -0,0 +1,44 @@
+import { afterEach, beforeEach, describe, expect, it } from 'bun:test';
+import type { DatabaseType } from '@caw/core';
+import { createRestApi } from '../api';
+import { apiRequest, createTestDb } from '../test-utils';
+import { createBroadcaster } from '../ws/broadcaster';
+
+describe('Event Routes', () => {
+  let db: DatabaseType;
+
+  beforeEach(() => {
+    db = createTestDb();
+  });
+
+  afterEach(() => {
+    db.close();
+  });
+
+  describe('GET /api/events', () => {
+    it('streams broadcast events', async () => {
+      const broadcaster = createBroadcaster();
+      const api = createRestApi(db, broadcaster);
+      const res = await api.handle(new Request('http://localhost/api/events'));
+
+      expect(res.status).toBe(200);
+      expect(res.headers.get('Content-Type')).toBe('text/event-stream');
+      const reader = (res.body as ReadableStream<Uint8Array>).getReader();
+      const decoder = new TextDecoder();
+      expect(decoder.decode((await reader.read()).value)).toBe(': connected\n\n');
+
+      broadcaster.emit('workflow:status', { id: 'wf_1', status: 'in_progress' });
+      const frame = decoder.decode((await reader.read()).value);
+      expect(frame).toBe(
+        'event: workflow:status\ndata: {"id":"wf_1","status":"in_progress"}\n\n',
+      );
+      await reader.cancel();
+    });
+
+    it('returns 404 without a broadcaster', async () => {
+      const api = createRestApi(db);
+      const res = await apiRequest(api.handle, 'GET', '/api/events');
+      expect(res.status).toBe(404);
+    });
+  });
+});
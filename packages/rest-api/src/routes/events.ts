@@ -0,0 +1,53 @@
+import { notFound } from '../response';
+import type { Router } from '../router';
+import type { Broadcaster } from '../ws/broadcaster';
+
+/** Comment frames sent while idle so the connection isn't timed out. */
+const KEEPALIVE_MS = 15_000;
+
+const encoder = new TextEncoder();
+
+export function registerEventRoutes(router: Router, broadcaster?: Broadcaster) {
+  // Server-sent events: every broadcast, as WebSocket clients on `global` get them
+  router.get('/api/events', (req) => {
+    if (!broadcaster) {
+      return notFound('Event stream is not available');
+    }
+
+    let cleanup = () => {};
+    const stream = new ReadableStream<Uint8Array>({
+      start(controller) {
+        const send = (frame: string) => {
+          try {
+            controller.enqueue(encoder.encode(frame));
+          } catch {
+            // Stream already closed
+            cleanup();
+          }
+        };
+        const unsubscribe = broadcaster.subscribe((type, data) => {
+          send(`event: ${type}\ndata: ${JSON.stringify(data)}\n\n`);
+        });
+        const keepalive = setInterval(() => send(': keepalive\n\n'), KEEPALIVE_MS);
+        cleanup = () => {
+          clearInterval(keepalive);
+          unsubscribe();
+        };
+        req.signal.addEventListener('abort', () => cleanup());
+        send(': connected\n\n');
+      },
+      cancel() {
+        cleanup();
+      },
+    });
+
+    return new Response(stream, {
+      status: 200,
+      headers: {
+        'Content-Type': 'text/event-stream',
+        'Cache-Control': 'no-cache',
+        Connection: 'keep-alive',
+      },
+    });
+  });
+}